@@ -14,121 +14,555 @@ mod alpeh_consensus_client {
     use super::pink;
     use crate::{
         traits::{FinalityError, FinalityGadget, StateTrieManager},
-        types::{ConsensusContractResult, ConsensusProofHandlerBuilder, ContractError},
+        types::{
+            grandpa_authority_set, import_next_authorities, update_session, verify_misbehaviour,
+            AuthoritySetRecord, ChainAuthorities, ChainId, ChainRecord, ConsensusAuthorities,
+            ConsensusContractResult, ConsensusEngine, ConsensusProofClientHandler,
+            ConsensusProofHandlerBuilder, ContractError, EmergencyPolicy, FinalityVersionsRecord,
+            HeaderRecord, MisbehaviourRecord, SessionRecord,
+        },
         ConsensusProof, StateTrieResponseProof,
     };
     use alloc::vec::Vec;
+    use ink::storage::Mapping;
+    use light_client::{
+        beefy::BeefyAuthoritySet,
+        grandpa::{crypto::AuthorityWeight, GrandpaAuthoritySet},
+        misbehaviour::Misbehaviour,
+        session::SessionProof,
+        version::FinalityVersionSchedule,
+        SessionIndex, VerifiedBlock,
+    };
     use pink::PinkEnvironment;
     use utils::types::{AccessControl, AuthorityId, SudoAccount};
 
+    /// Number of verified headers kept by default, for each chain
+    const DEFAULT_HEADER_RETENTION: u32 = 256;
+
     #[ink(storage)]
     pub struct AlephConsensusClient {
-        /// List of authorities for the consensus client
-        consensus_auth: ConsensusAuthorities,
-        /// Sudo account for emergency operations
-        sudo: SudoAccount,
+        /// Registers chains, each chain is then administered by its own sudo account
+        owner: SudoAccount,
+        /// Registered chains by chain id (genesis hash)
+        chains: Mapping<ChainId, ChainRecord>,
+        /// Ids of the registered chains, in order of registration
+        chain_ids: Vec<ChainId>,
+        /// Authorities of each chain, consensus proofs of Aleph chains are verified against their commitment
+        consensus_auth: Mapping<ChainId, ChainAuthorities>,
+        /// Verified headers by chain and block number, state proofs are only accepted under their state roots
+        headers: Mapping<(ChainId, u32), HeaderRecord>,
+        /// Block numbers of the stored headers of each chain, in increasing order
+        stored_heights: Mapping<ChainId, Vec<u32>>,
+        /// Maximum number of stored headers of each chain, the oldest ones are pruned first
+        header_retention: u32,
+    }
+
+    /// A chain has been registered along with its sudo account
+    #[ink(event)]
+    pub struct ChainRegistered {
+        #[ink(topic)]
+        chain: ChainId,
+        engine: ConsensusEngine,
+        sudo: AuthorityId,
+    }
+
+    /// The Aleph authorities of a chain have changed. The session is `None` in a permissioned setting
+    #[ink(event)]
+    pub struct AuthoritiesChanged {
+        #[ink(topic)]
+        chain: ChainId,
+        authority_set: AuthoritySetRecord,
+        session: Option<SessionIndex>,
     }
 
-    #[derive(Debug)]
-    #[ink::storage_item]
-    pub struct ConsensusAuthorities {
-        authorities: Vec<AuthorityId>,
-        next_authorities: Option<Vec<AuthorityId>>,
+    /// The GRANDPA authority set of a chain has changed
+    #[ink(event)]
+    pub struct GrandpaAuthoritiesChanged {
+        #[ink(topic)]
+        chain: ChainId,
+        #[ink(topic)]
+        set_id: u64,
+    }
+
+    /// The current BEEFY validator set of a chain has changed
+    #[ink(event)]
+    pub struct BeefyAuthoritiesChanged {
+        #[ink(topic)]
+        chain: ChainId,
+        #[ink(topic)]
+        set_id: u64,
+    }
+
+    /// The emergency finalizer account (aka sudo) of a chain has changed
+    #[ink(event)]
+    pub struct EmergencyFinalizerUpdated {
+        #[ink(topic)]
+        chain: ChainId,
+        previous: Option<AuthorityId>,
+        sudo: AuthorityId,
+    }
+
+    /// The sudo account of a chain has been removed, the chain is fully trustless
+    #[ink(event)]
+    pub struct SudoRemoved {
+        #[ink(topic)]
+        chain: ChainId,
+        previous: AuthorityId,
+    }
+
+    /// The policy on blocks of a chain finalized by the emergency finalizer has changed
+    #[ink(event)]
+    pub struct EmergencyPolicySet {
+        #[ink(topic)]
+        chain: ChainId,
+        policy: EmergencyPolicy,
+    }
+
+    /// A misbehaviour of the authorities of a chain has been proven, the chain is frozen
+    #[ink(event)]
+    pub struct MisbehaviourReported {
+        #[ink(topic)]
+        chain: ChainId,
+        reporter: AccountId,
+        misbehaviour: MisbehaviourRecord,
+    }
+
+    /// The schedule of the finality versions of a chain has changed
+    #[ink(event)]
+    pub struct FinalityVersionsSet {
+        #[ink(topic)]
+        chain: ChainId,
+        versions: FinalityVersionsRecord,
+    }
+
+    /// A frozen chain has been unfrozen by its sudo account, its proofs are verified again
+    #[ink(event)]
+    pub struct ChainUnfrozen {
+        #[ink(topic)]
+        chain: ChainId,
+    }
+
+    /// A header has been verified and stored, state proofs are accepted under its state root
+    #[ink(event)]
+    pub struct BlockImported {
+        #[ink(topic)]
+        chain: ChainId,
+        #[ink(topic)]
+        height: u32,
+        hash: [u8; 32],
+        emergency_finalized: bool,
+    }
+
+    /// The maximum number of stored headers of each chain has changed
+    #[ink(event)]
+    pub struct HeaderRetentionSet {
+        retention: u32,
     }
 
     impl AlephConsensusClient {
         #[ink(constructor)]
         pub fn new() -> Self {
-            // Set sudo account for initializing later on the consensus client authorities
-            // It can be removed at later stages for a permissionless setting
-            let sudo = pink::env().caller();
-            let sudo = SudoAccount::new(Some(sudo));
-
-            // Set an empty set of authorities
-            let consensus_auth = ConsensusAuthorities {
-                authorities: Default::default(),
-                next_authorities: Default::default(),
-            };
+            // The owner registers the chains along with their sudo accounts
+            let owner = pink::env().caller();
+            let owner = SudoAccount::new(Some(owner));
 
             Self {
-                consensus_auth,
-                sudo,
+                owner,
+                chains: Mapping::new(),
+                chain_ids: Vec::new(),
+                consensus_auth: Mapping::new(),
+                headers: Mapping::new(),
+                stored_heights: Mapping::new(),
+                header_retention: DEFAULT_HEADER_RETENTION,
+            }
+        }
+
+        fn chain_record(&self, chain: &ChainId) -> ConsensusContractResult<ChainRecord> {
+            self.chains.get(chain).ok_or(ContractError::UnknownChain)
+        }
+
+        /// Aleph authorities of the chain, empty if they are not set yet
+        fn aleph_authorities(&self, chain: &ChainId) -> ConsensusAuthorities {
+            match self.consensus_auth.get(chain) {
+                Some(ChainAuthorities::Aleph(consensus_auth)) => consensus_auth,
+                _ => Default::default(),
             }
         }
 
-        // Once it is permissionless modify this because the exception can be triggered
-        fn sudo(&self) -> AccountId {
-            self.sudo
-                .get()
-                .expect("The sudo account is expected to be initialized")
+        /// Stores the Aleph authorities of the chain along with their commitment
+        fn set_aleph_authorities(
+            &mut self,
+            chain: &ChainId,
+            mut record: ChainRecord,
+            consensus_auth: ConsensusAuthorities,
+        ) {
+            record.authority_set = AuthoritySetRecord::new(consensus_auth.authorities.clone());
+            self.chains.insert(chain, &record);
+
+            self.env().emit_event(AuthoritiesChanged {
+                chain: *chain,
+                authority_set: record.authority_set,
+                session: consensus_auth.session,
+            });
+            self.consensus_auth
+                .insert(chain, &ChainAuthorities::Aleph(consensus_auth));
+        }
+
+        /// Stores the GRANDPA authority set of the chain
+        fn set_grandpa_set(&mut self, chain: &ChainId, authority_set: &GrandpaAuthoritySet) {
+            self.consensus_auth
+                .insert(chain, &ChainAuthorities::grandpa(authority_set));
+
+            self.env().emit_event(GrandpaAuthoritiesChanged {
+                chain: *chain,
+                set_id: authority_set.set_id(),
+            });
+        }
+
+        /// Stores the current and next BEEFY validator sets of the chain
+        fn set_beefy_sets(
+            &mut self,
+            chain: &ChainId,
+            current: &BeefyAuthoritySet,
+            next: &BeefyAuthoritySet,
+        ) {
+            self.consensus_auth
+                .insert(chain, &ChainAuthorities::beefy(current, next));
+
+            self.env().emit_event(BeefyAuthoritiesChanged {
+                chain: *chain,
+                set_id: current.id,
+            });
+        }
+
+        fn beefy_sets(
+            &self,
+            chain: &ChainId,
+        ) -> ConsensusContractResult<(BeefyAuthoritySet, BeefyAuthoritySet)> {
+            self.consensus_auth
+                .get(chain)
+                .ok_or(ContractError::InvalidBeefyAuthorities)?
+                .beefy_sets()
+        }
+
+        fn grandpa_set(&self, chain: &ChainId) -> ConsensusContractResult<GrandpaAuthoritySet> {
+            self.consensus_auth
+                .get(chain)
+                .ok_or(ContractError::InvalidGrandpaAuthorities)?
+                .grandpa_set()
+        }
+
+        fn session_record(&self, chain: &ChainId) -> Option<SessionRecord> {
+            let consensus_auth = self.aleph_authorities(chain);
+            consensus_auth.session.map(|session| SessionRecord {
+                session,
+                authorities: consensus_auth.authorities,
+                next_authorities: consensus_auth.next_authorities,
+            })
+        }
+
+        fn set_session_record(
+            &mut self,
+            chain: &ChainId,
+            chain_record: ChainRecord,
+            record: SessionRecord,
+        ) {
+            self.set_aleph_authorities(
+                chain,
+                chain_record,
+                ConsensusAuthorities {
+                    authorities: record.authorities,
+                    next_authorities: record.next_authorities,
+                    session: Some(record.session),
+                },
+            );
+        }
+
+        fn consensus_handler(
+            &self,
+            request: ConsensusProof,
+        ) -> ConsensusContractResult<ConsensusProofClientHandler> {
+            let chain = self.verifiable_chain(&request.chain)?;
+            let builder = ConsensusProofHandlerBuilder::default();
+
+            let builder = match chain.engine {
+                ConsensusEngine::Aleph => {
+                    let (sudo, emergency_policy) = chain.emergency_finality();
+                    builder
+                        .setup_client(
+                            chain.authority_set,
+                            request.signers.clone(),
+                            sudo,
+                            chain.finality_versions,
+                        )?
+                        .consensus_proof(request)
+                        .emergency_policy(emergency_policy.into())
+                }
+                ConsensusEngine::Grandpa => {
+                    let authority_set = self.grandpa_set(&request.chain)?;
+                    builder
+                        .setup_grandpa_client(authority_set)
+                        .consensus_proof(request)
+                }
+                ConsensusEngine::Beefy => {
+                    let (current, next) = self.beefy_sets(&request.chain)?;
+                    builder
+                        .setup_beefy_client(current, next)
+                        .consensus_proof(request)
+                }
+            };
+
+            Ok(builder.build())
+        }
+
+        /// Stores a verified header of the chain and prunes the oldest ones beyond the retention.
+        /// A header conflicting with the one stored at the same height is rejected.
+        fn store_header(
+            &mut self,
+            chain: &ChainId,
+            record: HeaderRecord,
+        ) -> ConsensusContractResult<()> {
+            if let Some(stored) = self.headers.get((chain, record.height)) {
+                if stored.hash != record.hash {
+                    return Err(ContractError::ConflictingHeader);
+                }
+            }
+
+            let mut heights = self.stored_heights.get(chain).unwrap_or_default();
+            if let Err(position) = heights.binary_search(&record.height) {
+                heights.insert(position, record.height);
+            }
+            self.headers.insert((chain, record.height), &record);
+            self.stored_heights.insert(chain, &heights);
+            self.prune_headers(chain);
+            Ok(())
+        }
+
+        fn prune_headers(&mut self, chain: &ChainId) {
+            let mut heights = self.stored_heights.get(chain).unwrap_or_default();
+            let retention = self.header_retention as usize;
+            if heights.len() > retention {
+                let excess = heights.len() - retention;
+                for height in heights.drain(..excess) {
+                    self.headers.remove((chain, height));
+                }
+                self.stored_heights.insert(chain, &heights);
+            }
+        }
+
+        /// The chain is registered and not frozen
+        fn verifiable_chain(&self, chain: &ChainId) -> ConsensusContractResult<ChainRecord> {
+            let record = self.chain_record(chain)?;
+            if record.frozen.is_some() {
+                Err(ContractError::ClientFrozen)
+            } else {
+                Ok(record)
+            }
+        }
+
+        /// The chain is registered and not frozen, for the messages that update its authorities
+        fn updatable_chain(&self, chain: &ChainId) -> Result<ChainRecord, FinalityError> {
+            self.verifiable_chain(chain).map_err(|error| match error {
+                ContractError::ClientFrozen => FinalityError::ClientFrozen,
+                _ => FinalityError::UnknownChain,
+            })
         }
 
         fn ensure_owner(&self) -> ConsensusContractResult<()> {
-            AccessControl::new(self.sudo.get())
+            AccessControl::new(self.owner.get())
                 .caller(pink::env().caller())
                 .verify()
                 .map_err(|_| ContractError::BadOrigin)
         }
+
+        /// Returns the chain if the caller is its sudo account
+        fn ensure_chain_sudo(&self, chain: &ChainId) -> Result<ChainRecord, FinalityError> {
+            let record = self.chains.get(chain).ok_or(FinalityError::UnknownChain)?;
+            let sudo = record.sudo.ok_or(FinalityError::PermissionDenied)?;
+
+            AccessControl::new(Some(sudo))
+                .caller(pink::env().caller())
+                .verify()
+                .map_err(|_| FinalityError::PermissionDenied)?;
+            Ok(record)
+        }
     }
 
     impl FinalityGadget for AlephConsensusClient {
-        /// Initialize a permissionless setting for the consensus client, by rotating a committee of authorities
-        // Not implemeneted yet, we are still in a permissioned setting.
+        /// Registers a chain finalized by the given engine, along with its sudo account
+        #[ink(message)]
+        fn register_chain(
+            &mut self,
+            chain: ChainId,
+            engine: ConsensusEngine,
+            sudo: AuthorityId,
+        ) -> Result<(), FinalityError> {
+            self.ensure_owner()
+                .map_err(|_| FinalityError::PermissionDenied)?;
+            if self.chains.contains(chain) {
+                return Err(FinalityError::ChainAlreadyRegistered);
+            }
+
+            self.chains.insert(chain, &ChainRecord::new(engine, sudo));
+            self.chain_ids.push(chain);
+
+            self.env().emit_event(ChainRegistered {
+                chain,
+                engine,
+                sudo,
+            });
+            Ok(())
+        }
+
+        /// Returns the ids of the registered chains
+        #[ink(message)]
+        fn chains(&self) -> Vec<ChainId> {
+            self.chain_ids.clone()
+        }
+
+        /// Returns the registry entry of the chain, if any
+        #[ink(message)]
+        fn chain(&self, chain: ChainId) -> Option<ChainRecord> {
+            self.chains.get(chain)
+        }
+
+        /// Initialize a permissionless setting for an Aleph chain, by rotating a committee of authorities.
+        /// The proof of the next authorities must be under a block of the given session, finalized by the current authorities.
         #[ink(message)]
         fn initialize_permissionless_authorities(
             &mut self,
-            _next_authorities: Vec<AuthorityId>,
+            chain: ChainId,
+            session: SessionIndex,
+            proof: SessionProof,
         ) -> Result<(), FinalityError> {
+            self.ensure_chain_sudo(&chain)?;
+            let chain_record = self.updatable_chain(&chain)?;
+            if chain_record.engine != ConsensusEngine::Aleph {
+                return Err(FinalityError::UnsupportedEngine);
+            }
+
+            let authorities = self.aleph_authorities(&chain).authorities;
+            let record = import_next_authorities(session, authorities, proof)
+                .map_err(|_| FinalityError::InvalidProof)?;
+            self.set_session_record(&chain, chain_record, record);
             Ok(())
         }
 
-        /// Set a permissioned set of authorities for the consensus client
+        /// Set a permissioned set of authorities for an Aleph chain, it leaves the permissionless setting
         #[ink(message)]
         fn set_permissioned_authorities(
             &mut self,
+            chain: ChainId,
             authorities: Vec<AuthorityId>,
         ) -> Result<(), FinalityError> {
-            self.ensure_owner()
-                .map_err(|_| FinalityError::PermissionDenied)?;
-            self.consensus_auth.authorities = authorities;
+            let chain_record = self.ensure_chain_sudo(&chain)?;
+            if chain_record.engine != ConsensusEngine::Aleph {
+                return Err(FinalityError::UnsupportedEngine);
+            }
+
+            self.set_aleph_authorities(
+                &chain,
+                chain_record,
+                ConsensusAuthorities {
+                    authorities,
+                    next_authorities: None,
+                    session: None,
+                },
+            );
+            Ok(())
+        }
+
+        /// Set the GRANDPA authority set of a chain, it then follows the changes signaled by imported headers
+        #[ink(message)]
+        fn set_grandpa_authorities(
+            &mut self,
+            chain: ChainId,
+            set_id: u64,
+            authorities: Vec<(AuthorityId, AuthorityWeight)>,
+        ) -> Result<(), FinalityError> {
+            let chain_record = self.ensure_chain_sudo(&chain)?;
+            if chain_record.engine != ConsensusEngine::Grandpa {
+                return Err(FinalityError::UnsupportedEngine);
+            }
+
+            let authority_set = grandpa_authority_set(set_id, authorities);
+            authority_set
+                .threshold()
+                .map_err(|_| FinalityError::InvalidAuthorities)?;
+            self.set_grandpa_set(&chain, &authority_set);
+            Ok(())
+        }
+
+        /// Set the current and next BEEFY validator sets of a chain, they then follow the changes of imported commitments
+        #[ink(message)]
+        fn set_beefy_authorities(
+            &mut self,
+            chain: ChainId,
+            current: BeefyAuthoritySet,
+            next: BeefyAuthoritySet,
+        ) -> Result<(), FinalityError> {
+            let chain_record = self.ensure_chain_sudo(&chain)?;
+            if chain_record.engine != ConsensusEngine::Beefy {
+                return Err(FinalityError::UnsupportedEngine);
+            }
+            for set in [current, next] {
+                set.threshold()
+                    .map_err(|_| FinalityError::InvalidAuthorities)?;
+            }
+
+            self.set_beefy_sets(&chain, &current, &next);
             Ok(())
         }
 
-        /// Updates the list of authorities based on next authorities already stored and stores the new next authorities within a proof.
-        // Not implemeneted yet, we are still in a permissioned setting.
+        /// Updates the list of authorities of an Aleph chain based on next authorities already stored and stores the new next authorities within a proof.
+        /// Anyone can submit the proof, since it is verified against the trusted authorities.
         #[ink(message)]
         fn update_authorities(
             &mut self,
-            _next_authorities: Vec<AuthorityId>,
-            _proof: light_client::GetResponse,
+            chain: ChainId,
+            proof: SessionProof,
         ) -> Result<(), FinalityError> {
+            let chain_record = self.updatable_chain(&chain)?;
+            let record = self
+                .session_record(&chain)
+                .ok_or(FinalityError::NotPermissionless)?;
+
+            let record = update_session(record, proof).map_err(|_| FinalityError::InvalidProof)?;
+            self.set_session_record(&chain, chain_record, record);
             Ok(())
         }
 
-        /// Set the next emergency finalizer account (aka sudo)
+        /// Set the next emergency finalizer account (aka sudo) of a chain
         #[ink(message)]
         fn update_emergency_finalizer_account(
             &mut self,
+            chain: ChainId,
             emergency_finalizer: AuthorityId,
         ) -> Result<(), FinalityError> {
-            self.ensure_owner()
-                .map_err(|_| FinalityError::PermissionDenied)?;
+            let mut chain_record = self.ensure_chain_sudo(&chain)?;
+
+            let previous = chain_record.sudo.replace(emergency_finalizer);
+            self.chains.insert(chain, &chain_record);
 
-            self.sudo.set(emergency_finalizer);
+            self.env().emit_event(EmergencyFinalizerUpdated {
+                chain,
+                previous,
+                sudo: emergency_finalizer,
+            });
             Ok(())
         }
 
-        /// Returns the current session, if necessary.
-        // Returns `None` because we are still in a permissioned setting
+        /// Returns the current session of an Aleph chain, if necessary.
+        /// Returns `None` in a permissioned setting.
         #[ink(message)]
-        fn current_session(&self) -> Option<light_client::SessionIndex> {
-            None
+        fn current_session(&self, chain: ChainId) -> Option<SessionIndex> {
+            self.aleph_authorities(&chain).session
         }
 
-        /// Returns the current session, if necessary.
+        /// Returns the current authorities of a chain.
         #[ink(message)]
-        fn authorities(&self) -> Option<Vec<AccountId>> {
-            let auth = self.consensus_auth.authorities.clone();
+        fn authorities(&self, chain: ChainId) -> Option<Vec<AccountId>> {
+            let auth = self.consensus_auth.get(chain)?.accounts();
             if auth.is_empty() {
                 None
             } else {
@@ -136,45 +570,266 @@ mod alpeh_consensus_client {
             }
         }
 
-        /// Checks whether the chain is in a permissionless setting
+        /// Returns the commitment to the current authorities of an Aleph chain, signers of consensus proofs are proven against it
         #[ink(message)]
-        fn is_permissionless(&self) -> bool {
-            self.consensus_auth.next_authorities.is_some()
+        fn authority_set(&self, chain: ChainId) -> Option<AuthoritySetRecord> {
+            self.chains.get(chain).map(|record| record.authority_set)
         }
 
-        /// Retuns current sudo account. Returns `None` if there isnt't
+        /// Checks whether the chain is in a permissionless setting.
+        /// GRANDPA and BEEFY chains are, once their authorities are set, since they follow the changes of imported headers.
         #[ink(message)]
-        fn sudo(&self) -> Option<AuthorityId> {
-            self.sudo.get()
+        fn is_permissionless(&self, chain: ChainId) -> bool {
+            match self.consensus_auth.get(chain) {
+                Some(ChainAuthorities::Aleph(consensus_auth)) => consensus_auth.session.is_some(),
+                Some(ChainAuthorities::Grandpa(_)) | Some(ChainAuthorities::Beefy(_)) => true,
+                None => false,
+            }
         }
 
-        /// Removes sudo account, if some.
+        /// Sets the policy on blocks of a chain finalized by the emergency finalizer
         #[ink(message)]
-        fn remove_sudo(&mut self) -> Result<(), FinalityError> {
-            self.ensure_owner()
-                .map_err(|_| FinalityError::PermissionDenied)?;
+        fn set_emergency_policy(
+            &mut self,
+            chain: ChainId,
+            policy: EmergencyPolicy,
+        ) -> Result<(), FinalityError> {
+            let mut chain_record = self.ensure_chain_sudo(&chain)?;
+
+            chain_record.emergency_policy = policy.clone();
+            self.chains.insert(chain, &chain_record);
 
-            self.sudo.remove();
+            self.env().emit_event(EmergencyPolicySet { chain, policy });
             Ok(())
         }
 
+        /// Returns the policy on blocks of a chain finalized by the emergency finalizer
         #[ink(message)]
-        fn verify_consensus(&self, request: ConsensusProof) -> ConsensusContractResult<()> {
-            let sudo = self.sudo();
+        fn emergency_policy(&self, chain: ChainId) -> Option<EmergencyPolicy> {
+            self.chains.get(chain).map(|record| record.emergency_policy)
+        }
+
+        /// Sets the schedule of the finality versions of an Aleph chain.
+        /// From the session of a version change on, justifications must be encoded with exactly the incoming version.
+        #[ink(message)]
+        fn set_finality_versions(
+            &mut self,
+            chain: ChainId,
+            versions: FinalityVersionsRecord,
+        ) -> Result<(), FinalityError> {
+            let mut chain_record = self.ensure_chain_sudo(&chain)?;
+            if chain_record.engine != ConsensusEngine::Aleph {
+                return Err(FinalityError::UnsupportedEngine);
+            }
+            FinalityVersionSchedule::try_from(versions.clone())
+                .map_err(|_| FinalityError::InvalidVersionSchedule)?;
+
+            chain_record.finality_versions = versions.clone();
+            self.chains.insert(chain, &chain_record);
+
+            self.env()
+                .emit_event(FinalityVersionsSet { chain, versions });
+            Ok(())
+        }
+
+        /// Returns the schedule of the finality versions of a chain
+        #[ink(message)]
+        fn finality_versions(&self, chain: ChainId) -> Option<FinalityVersionsRecord> {
+            self.chains
+                .get(chain)
+                .map(|record| record.finality_versions)
+        }
+
+        /// Retuns current sudo account of a chain. Returns `None` if there isnt't
+        #[ink(message)]
+        fn sudo(&self, chain: ChainId) -> Option<AuthorityId> {
+            self.chains.get(chain).and_then(|record| record.sudo)
+        }
+
+        /// Removes the sudo account of a chain, if some.
+        /// Authorities must be rotated permissionlessly, the chain is then fully trustless.
+        #[ink(message)]
+        fn remove_sudo(&mut self, chain: ChainId) -> Result<(), FinalityError> {
+            let mut chain_record = self.ensure_chain_sudo(&chain)?;
+            if !self.is_permissionless(chain) {
+                return Err(FinalityError::NotPermissionless);
+            }
+
+            let previous = chain_record
+                .sudo
+                .take()
+                .ok_or(FinalityError::PermissionDenied)?;
+            self.chains.insert(chain, &chain_record);
+
+            self.env().emit_event(SudoRemoved { chain, previous });
+            Ok(())
+        }
+
+        /// Verifies evidence of two conflicting headers finalized by the authorities of an Aleph chain, and freezes the chain
+        #[ink(message)]
+        fn report_misbehaviour(
+            &mut self,
+            chain: ChainId,
+            misbehaviour: Misbehaviour,
+        ) -> ConsensusContractResult<()> {
+            let mut chain_record = self.verifiable_chain(&chain)?;
+            if chain_record.engine != ConsensusEngine::Aleph {
+                return Err(ContractError::UnsupportedEngine);
+            }
+
+            let (sudo, _) = chain_record.emergency_finality();
+            let authorities = self.aleph_authorities(&chain).authorities;
+            let record = verify_misbehaviour(authorities, sudo, &misbehaviour)?;
+
+            chain_record.frozen = Some(record.clone());
+            self.chains.insert(chain, &chain_record);
+
+            self.env().emit_event(MisbehaviourReported {
+                chain,
+                reporter: self.env().caller(),
+                misbehaviour: record,
+            });
+            Ok(())
+        }
+
+        /// Returns the conflicting headers that froze the chain, if any
+        #[ink(message)]
+        fn frozen(&self, chain: ChainId) -> Option<MisbehaviourRecord> {
+            self.chains.get(chain).and_then(|record| record.frozen)
+        }
+
+        /// Unfreezes a chain, authorities are expected to be reviewed beforehand
+        #[ink(message)]
+        fn unfreeze(&mut self, chain: ChainId) -> Result<(), FinalityError> {
+            let mut chain_record = self.ensure_chain_sudo(&chain)?;
 
+            chain_record.frozen = None;
+            self.chains.insert(chain, &chain_record);
+
+            self.env().emit_event(ChainUnfrozen { chain });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn verify_consensus(&self, request: ConsensusProof) -> ConsensusContractResult<()> {
             // Verify the consensus proof
-            let state_client_handler = ConsensusProofHandlerBuilder::default()
-                .setup_client(
-                    self.consensus_auth.authorities.clone(),
-                    request.untrusted_auth.clone(),
-                    sudo,
-                )?
-                .consensus_proof(request)
-                .build();
+            let state_client_handler = self.consensus_handler(request)?;
 
             state_client_handler.verify_consensus_state()?;
             Ok(())
         }
+
+        #[ink(message)]
+        fn verify_block(
+            &self,
+            request: ConsensusProof,
+            timestamp: StateTrieResponseProof,
+        ) -> ConsensusContractResult<VerifiedBlock> {
+            if timestamp.chain != request.chain {
+                return Err(ContractError::ChainMismatch);
+            }
+            let state_client_handler = self.consensus_handler(request)?;
+
+            state_client_handler.verify_block(&timestamp)
+        }
+
+        /// Verifies a consensus proof, the timestamp and a state proof under the state root of the finalized header, all at once.
+        /// The state proof must be about the given storage key, the verified block and the value are returned.
+        #[ink(message)]
+        fn verify_consensus_and_state(
+            &self,
+            request: ConsensusProof,
+            timestamp: StateTrieResponseProof,
+            state: StateTrieResponseProof,
+            key: Vec<u8>,
+        ) -> ConsensusContractResult<(VerifiedBlock, Vec<u8>)> {
+            if timestamp.chain != request.chain {
+                return Err(ContractError::ChainMismatch);
+            }
+            let state_client_handler = self.consensus_handler(request)?;
+
+            state_client_handler.verify_block_and_state(&timestamp, &state, &key)
+        }
+
+        /// Verifies a consensus proof along with the timestamp of the finalized block, and stores the verified header.
+        /// The GRANDPA authority set follows the changes signaled by the header, BEEFY validator sets the changes of the commitment.
+        #[ink(message)]
+        fn import_block(
+            &mut self,
+            request: ConsensusProof,
+            timestamp: StateTrieResponseProof,
+        ) -> ConsensusContractResult<HeaderRecord> {
+            if timestamp.chain != request.chain {
+                return Err(ContractError::ChainMismatch);
+            }
+            let chain = request.chain;
+            let state_client_handler = self.consensus_handler(request)?;
+            let (hash, block) = state_client_handler.verify_header(&timestamp)?;
+            let record = HeaderRecord::new(hash, block);
+            self.store_header(&chain, record)?;
+
+            if let Some(authority_set) = state_client_handler.next_grandpa_set()? {
+                let previous = self.grandpa_set(&chain)?;
+                if authority_set.set_id() != previous.set_id() {
+                    self.set_grandpa_set(&chain, &authority_set);
+                } else {
+                    // Pending changes are tracked silently
+                    self.consensus_auth
+                        .insert(chain, &ChainAuthorities::grandpa(&authority_set));
+                }
+            }
+            if let Some((current, next)) = state_client_handler.next_beefy_sets()? {
+                let (previous, _) = self.beefy_sets(&chain)?;
+                if current.id != previous.id {
+                    self.set_beefy_sets(&chain, &current, &next);
+                } else {
+                    // Announced sets are tracked silently
+                    self.consensus_auth
+                        .insert(chain, &ChainAuthorities::beefy(&current, &next));
+                }
+            }
+
+            self.env().emit_event(BlockImported {
+                chain,
+                height: record.height,
+                hash: record.hash,
+                emergency_finalized: record.emergency_finalized,
+            });
+            Ok(record)
+        }
+
+        /// Returns the stored header of a chain with the highest block number, if any
+        #[ink(message)]
+        fn latest_checkpoint(&self, chain: ChainId) -> Option<HeaderRecord> {
+            self.stored_heights
+                .get(chain)?
+                .last()
+                .and_then(|height| self.headers.get((chain, *height)))
+        }
+
+        /// Returns the stored header of a chain at the given block number, if any
+        #[ink(message)]
+        fn header(&self, chain: ChainId, height: u32) -> Option<HeaderRecord> {
+            self.headers.get((chain, height))
+        }
+
+        /// Sets the maximum number of stored headers of each chain, at least one. The oldest ones are pruned if needed
+        #[ink(message)]
+        fn set_header_retention(&mut self, retention: u32) -> Result<(), FinalityError> {
+            self.ensure_owner()
+                .map_err(|_| FinalityError::PermissionDenied)?;
+            if retention == 0 {
+                return Err(FinalityError::InvalidRetention);
+            }
+
+            self.header_retention = retention;
+            for chain in self.chain_ids.clone() {
+                self.prune_headers(&chain);
+            }
+
+            self.env().emit_event(HeaderRetentionSet { retention });
+            Ok(())
+        }
     }
 
     impl StateTrieManager for AlephConsensusClient {
@@ -183,9 +838,757 @@ mod alpeh_consensus_client {
             &self,
             request: StateTrieResponseProof,
         ) -> ConsensusContractResult<Vec<u8>> {
+            self.verifiable_chain(&request.chain)?;
+
+            // The state root must be the one of a verified header of the chain
+            let height = u32::try_from(request.proof.height())
+                .map_err(|_| ContractError::UnverifiedStateRoot)?;
+            match self.headers.get((request.chain, height)) {
+                Some(header) if header.is_root_of(&request) => (),
+                _ => return Err(ContractError::UnverifiedStateRoot),
+            }
+
             Ok(request
                 .verify_state()
                 .map_err(|_| ContractError::ConsensusClientInvalidStateProof)?)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::types::ConsensusState;
+        use ink::env::{test, DefaultEnvironment};
+        use light_client::{
+            authority_set::AuthoritySetCommitment,
+            backend::{HeaderHasher, NativeBackend, SignatureVerifier},
+            finality::justification::{AlephJustification, Version},
+            misbehaviour::FinalizedHeader,
+            state::timestamp_entry,
+            test_utils::{
+                self, encode_justification, JustificationEncoding, MockCommittee, MockState,
+            },
+            Hash, Header,
+        };
+        use pink::chain_extension::mock as pink_mock;
+        use scale::Encode;
+
+        const CHAIN: ChainId = [1; 32];
+
+        fn accounts() -> test::DefaultAccounts<DefaultEnvironment> {
+            test::default_accounts::<DefaultEnvironment>()
+        }
+
+        fn set_caller(account: AccountId) {
+            test::set_caller::<DefaultEnvironment>(account);
+        }
+
+        /// Contract deployed by alice, with a chain administered by bob, who is the caller
+        fn contract_with_chain(engine: ConsensusEngine) -> AlephConsensusClient {
+            let accounts = accounts();
+            set_caller(accounts.alice);
+            let mut contract = AlephConsensusClient::new();
+            contract
+                .register_chain(CHAIN, engine, accounts.bob)
+                .unwrap();
+            set_caller(accounts.bob);
+
+            contract
+        }
+
+        /// Signatures are verified natively in place of the pink chain extension
+        fn mock_signatures() {
+            pink_mock::mock_verify(|_, public, message, signature| {
+                NativeBackend.verify_ed25519(public, message, signature)
+            });
+        }
+
+        /// Accounts of the members of the committee, as they are stored by the contract
+        fn members(committee: &MockCommittee) -> Vec<AuthorityId> {
+            committee
+                .authorities()
+                .iter()
+                .map(|key| {
+                    let raw: &[u8] = key.as_ref();
+                    AuthorityId::try_from(raw).unwrap()
+                })
+                .collect()
+        }
+
+        fn consensus_proof(header: &Header, justification: Vec<u8>) -> ConsensusProof {
+            ConsensusProof {
+                chain: CHAIN,
+                justification,
+                state: ConsensusState {
+                    block: header.number,
+                    extrinsics_root: header.extrinsics_root,
+                    state_root: header.state_root,
+                    parent_hash: header.parent_hash,
+                    digest: header.digest.encode(),
+                },
+                signers: Vec::new(),
+                emergency_confirmation: None,
+            }
+        }
+
+        /// Consensus proof of the header finalized by the committee, along with the proofs of its signers
+        fn finalized_proof(committee: &MockCommittee, header: &Header) -> ConsensusProof {
+            let signatures = committee.sign(&NativeBackend.hash_header(header));
+            let justification = encode_justification(
+                AlephJustification::CommitteeMultisignature(signatures.clone()),
+                JustificationEncoding::Versioned(Version(3)),
+            )
+            .unwrap();
+
+            ConsensusProof {
+                signers: AuthoritySetCommitment::signer_proofs(
+                    &committee.authorities(),
+                    &signatures,
+                )
+                .unwrap(),
+                ..consensus_proof(header, justification)
+            }
+        }
+
+        /// Imports the header at the given height whose state is the given one
+        fn import(
+            contract: &mut AlephConsensusClient,
+            committee: &MockCommittee,
+            state: &MockState,
+            height: u32,
+        ) -> ConsensusContractResult<HeaderRecord> {
+            let header = test_utils::header(height, Hash::zero(), state.state_root(), Vec::new());
+
+            contract.import_block(
+                finalized_proof(committee, &header),
+                timestamp_proof(state, height),
+            )
+        }
+
+        fn state_proof(state: &MockState, key: &[u8], height: u32) -> StateTrieResponseProof {
+            StateTrieResponseProof::new(CHAIN, state.read_proof(&[key.to_vec()], height)).unwrap()
+        }
+
+        fn timestamp_proof(state: &MockState, height: u32) -> StateTrieResponseProof {
+            state_proof(state, timestamp_entry().key(), height)
+        }
+
+        /// Aleph chain finalized by the committee, signatures are verified natively
+        fn contract_with_committee(committee: &MockCommittee) -> AlephConsensusClient {
+            mock_signatures();
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            contract
+                .set_permissioned_authorities(CHAIN, members(committee))
+                .unwrap();
+
+            contract
+        }
+
+        #[ink::test]
+        fn chains_are_registered_once_by_the_owner() {
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let accounts = accounts();
+            let other = [2; 32];
+
+            assert_eq!(
+                contract.register_chain(other, ConsensusEngine::Grandpa, accounts.bob),
+                Err(FinalityError::PermissionDenied)
+            );
+            set_caller(accounts.alice);
+            assert_eq!(
+                contract.register_chain(CHAIN, ConsensusEngine::Grandpa, accounts.charlie),
+                Err(FinalityError::ChainAlreadyRegistered)
+            );
+            assert_eq!(
+                contract.chain(CHAIN),
+                Some(ChainRecord::new(ConsensusEngine::Aleph, accounts.bob))
+            );
+
+            assert_eq!(
+                contract.register_chain(other, ConsensusEngine::Grandpa, accounts.charlie),
+                Ok(())
+            );
+            assert_eq!(contract.chains(), vec![CHAIN, other]);
+        }
+
+        #[ink::test]
+        fn chains_are_administered_by_their_own_sudo() {
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let accounts = accounts();
+            let other = [2; 32];
+            let committee = MockCommittee::new(1, 4);
+            set_caller(accounts.alice);
+            contract
+                .register_chain(other, ConsensusEngine::Aleph, accounts.charlie)
+                .unwrap();
+
+            // Neither the sudo of another chain nor the owner administer a chain
+            for caller in [accounts.bob, accounts.alice] {
+                set_caller(caller);
+                assert_eq!(
+                    contract.set_permissioned_authorities(other, members(&committee)),
+                    Err(FinalityError::PermissionDenied)
+                );
+                assert_eq!(
+                    contract.set_emergency_policy(other, Default::default()),
+                    Err(FinalityError::PermissionDenied)
+                );
+                assert_eq!(
+                    contract.update_emergency_finalizer_account(other, caller),
+                    Err(FinalityError::PermissionDenied)
+                );
+                assert_eq!(
+                    contract.unfreeze(other),
+                    Err(FinalityError::PermissionDenied)
+                );
+            }
+            assert_eq!(
+                contract.set_permissioned_authorities([3; 32], members(&committee)),
+                Err(FinalityError::UnknownChain)
+            );
+
+            let authorities = contract.authority_set(CHAIN);
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_permissioned_authorities(other, members(&committee)),
+                Ok(())
+            );
+            assert_eq!(contract.authorities(other), Some(members(&committee)));
+            assert_eq!(contract.authority_set(CHAIN), authorities);
+
+            // The sudo account of a chain is handed over without affecting the other chains
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.update_emergency_finalizer_account(CHAIN, accounts.django),
+                Ok(())
+            );
+            assert_eq!(contract.sudo(CHAIN), Some(accounts.django));
+            assert_eq!(contract.sudo(other), Some(accounts.charlie));
+            assert_eq!(
+                contract.set_permissioned_authorities(CHAIN, members(&committee)),
+                Err(FinalityError::PermissionDenied)
+            );
+        }
+
+        #[ink::test]
+        fn aleph_messages_are_unsupported_by_other_engines() {
+            let committee = MockCommittee::new(1, 4);
+            let finalized = |state_root: Hash| {
+                let header = test_utils::header(7, Hash::zero(), state_root, Vec::new());
+                FinalizedHeader {
+                    justification: committee.sign(&NativeBackend.hash_header(&header)),
+                    header,
+                }
+            };
+            let misbehaviour = Misbehaviour::detect(
+                finalized(Hash::repeat_byte(1)),
+                finalized(Hash::repeat_byte(2)),
+                &NativeBackend,
+            )
+            .unwrap();
+
+            for engine in [ConsensusEngine::Grandpa, ConsensusEngine::Beefy] {
+                let mut contract = contract_with_chain(engine);
+                let proof = test_utils::session_proof(&committee, 10, 1, &committee.authorities());
+                assert_eq!(
+                    contract.initialize_permissionless_authorities(CHAIN, 1, proof),
+                    Err(FinalityError::UnsupportedEngine)
+                );
+                assert_eq!(
+                    contract.set_permissioned_authorities(CHAIN, members(&committee)),
+                    Err(FinalityError::UnsupportedEngine)
+                );
+                assert_eq!(
+                    contract.set_finality_versions(CHAIN, Default::default()),
+                    Err(FinalityError::UnsupportedEngine)
+                );
+                assert_eq!(
+                    contract.report_misbehaviour(CHAIN, misbehaviour.clone()),
+                    Err(ContractError::UnsupportedEngine)
+                );
+            }
+        }
+
+        #[ink::test]
+        fn finality_versions_are_set_by_the_chain_sudo() {
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let versions = FinalityVersionsRecord {
+                session_period: 900,
+                changes: vec![(3, 2), (5, 3)],
+            };
+            assert_eq!(
+                contract.set_finality_versions(CHAIN, versions.clone()),
+                Ok(())
+            );
+            assert_eq!(contract.finality_versions(CHAIN), Some(versions));
+
+            // Changes must be in increasing session order, and they need a session period
+            let unordered = FinalityVersionsRecord {
+                session_period: 900,
+                changes: vec![(5, 3), (3, 2)],
+            };
+            assert_eq!(
+                contract.set_finality_versions(CHAIN, unordered),
+                Err(FinalityError::InvalidVersionSchedule)
+            );
+            let without_period = FinalityVersionsRecord {
+                session_period: 0,
+                changes: vec![(3, 2)],
+            };
+            assert_eq!(
+                contract.set_finality_versions(CHAIN, without_period),
+                Err(FinalityError::InvalidVersionSchedule)
+            );
+
+            set_caller(accounts().charlie);
+            assert_eq!(
+                contract.set_finality_versions(CHAIN, Default::default()),
+                Err(FinalityError::PermissionDenied)
+            );
+        }
+
+        #[ink::test]
+        fn grandpa_authority_sets_must_have_weight() {
+            let mut contract = contract_with_chain(ConsensusEngine::Grandpa);
+            let key = AuthorityId::from([7; 32]);
+
+            assert_eq!(
+                contract.set_grandpa_authorities(CHAIN, 1, Vec::new()),
+                Err(FinalityError::InvalidAuthorities)
+            );
+            assert_eq!(
+                contract.set_grandpa_authorities(CHAIN, 1, vec![(key, 0)]),
+                Err(FinalityError::InvalidAuthorities)
+            );
+            assert_eq!(
+                contract.grandpa_set(&CHAIN),
+                Err(ContractError::InvalidGrandpaAuthorities)
+            );
+
+            assert_eq!(
+                contract.set_grandpa_authorities(CHAIN, 1, vec![(key, 1)]),
+                Ok(())
+            );
+            assert_eq!(
+                contract.grandpa_set(&CHAIN),
+                Ok(grandpa_authority_set(1, vec![(key, 1)]))
+            );
+        }
+
+        #[ink::test]
+        fn beefy_validator_sets_are_set_by_the_chain_sudo() {
+            let mut contract = contract_with_chain(ConsensusEngine::Beefy);
+            let current = BeefyAuthoritySet {
+                id: 3,
+                len: 4,
+                keyset_commitment: Hash::repeat_byte(3),
+            };
+            let next = BeefyAuthoritySet { id: 4, ..current };
+
+            // Nothing is verified until the validator sets are set
+            let block = test_utils::header(10, Hash::zero(), Hash::zero(), Vec::new());
+            assert_eq!(
+                contract.verify_consensus(consensus_proof(&block, Vec::new())),
+                Err(ContractError::InvalidBeefyAuthorities)
+            );
+            assert!(!contract.is_permissionless(CHAIN));
+
+            assert_eq!(contract.set_beefy_authorities(CHAIN, current, next), Ok(()));
+            assert!(contract.is_permissionless(CHAIN));
+            assert_eq!(
+                contract.verify_consensus(consensus_proof(&block, vec![1, 2, 3])),
+                Err(ContractError::ConsensusClientInvalidJustifications)
+            );
+
+            set_caller(accounts().charlie);
+            assert_eq!(
+                contract.set_beefy_authorities(CHAIN, current, next),
+                Err(FinalityError::PermissionDenied)
+            );
+
+            // Empty validator sets can not sign commitments
+            set_caller(accounts().bob);
+            let empty = BeefyAuthoritySet { len: 0, ..next };
+            assert_eq!(
+                contract.set_beefy_authorities(CHAIN, current, empty),
+                Err(FinalityError::InvalidAuthorities)
+            );
+            assert_eq!(
+                contract.set_beefy_authorities(CHAIN, empty, next),
+                Err(FinalityError::InvalidAuthorities)
+            );
+
+            let mut contract = contract_with_chain(ConsensusEngine::Grandpa);
+            assert_eq!(
+                contract.set_beefy_authorities(CHAIN, current, next),
+                Err(FinalityError::UnsupportedEngine)
+            );
+        }
+
+        #[ink::test]
+        fn legacy_justifications_are_rejected_after_the_switch_over() {
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let committee = MockCommittee::new(1, 4);
+            let block = test_utils::header(900, Hash::zero(), Hash::zero(), Vec::new());
+            let legacy = encode_justification(
+                committee.justification(&block),
+                JustificationEncoding::Legacy(Version(2)),
+            )
+            .unwrap();
+
+            // Without a schedule, legacy justifications are decoded (the committee is unknown to the chain though)
+            assert_eq!(
+                contract.verify_consensus(consensus_proof(&block, legacy.clone())),
+                Err(ContractError::ConsensusClientInvalidSignatures)
+            );
+
+            // From the session of the change on, they must be encoded with the incoming version
+            let versions = FinalityVersionsRecord {
+                session_period: 900,
+                changes: vec![(1, 3)],
+            };
+            contract.set_finality_versions(CHAIN, versions).unwrap();
+            assert_eq!(
+                contract.verify_consensus(consensus_proof(&block, legacy)),
+                Err(ContractError::ConsensusClientInvalidJustifications)
+            );
+        }
+
+        #[ink::test]
+        fn authorities_rotate_permissionlessly_once_initialized() {
+            mock_signatures();
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let current = MockCommittee::new(1, 4);
+            let next = MockCommittee::new(2, 4);
+            let following = MockCommittee::new(3, 4);
+            contract
+                .set_permissioned_authorities(CHAIN, members(&current))
+                .unwrap();
+
+            // Permissioned authorities are only rotated by the sudo account
+            let rotation = test_utils::session_proof(&next, 20, 2, &following.authorities());
+            assert_eq!(
+                contract.update_authorities(CHAIN, rotation.clone()),
+                Err(FinalityError::NotPermissionless)
+            );
+            assert_eq!(
+                contract.remove_sudo(CHAIN),
+                Err(FinalityError::NotPermissionless)
+            );
+
+            let initialization = test_utils::session_proof(&current, 10, 1, &next.authorities());
+            set_caller(accounts().charlie);
+            assert_eq!(
+                contract.initialize_permissionless_authorities(CHAIN, 1, initialization.clone()),
+                Err(FinalityError::PermissionDenied)
+            );
+            set_caller(accounts().bob);
+            let wrong_session = test_utils::session_proof(&current, 10, 2, &next.authorities());
+            assert_eq!(
+                contract.initialize_permissionless_authorities(CHAIN, 1, wrong_session),
+                Err(FinalityError::InvalidProof)
+            );
+            assert_eq!(
+                contract.initialize_permissionless_authorities(CHAIN, 1, initialization),
+                Ok(())
+            );
+            assert_eq!(contract.current_session(CHAIN), Some(1));
+            assert!(contract.is_permissionless(CHAIN));
+
+            // Anyone rotates the authorities with a block of the next session, finalized by the next authorities
+            set_caller(accounts().charlie);
+            let old_set = test_utils::session_proof(&current, 20, 2, &following.authorities());
+            assert_eq!(
+                contract.update_authorities(CHAIN, old_set),
+                Err(FinalityError::InvalidProof)
+            );
+            assert_eq!(contract.update_authorities(CHAIN, rotation), Ok(()));
+            assert_eq!(contract.current_session(CHAIN), Some(2));
+            assert_eq!(contract.authorities(CHAIN), Some(members(&next)));
+            assert_eq!(
+                contract.authority_set(CHAIN),
+                Some(AuthoritySetRecord::new(members(&next)))
+            );
+
+            // Then the sudo account can be removed, only by itself
+            assert_eq!(
+                contract.remove_sudo(CHAIN),
+                Err(FinalityError::PermissionDenied)
+            );
+            set_caller(accounts().bob);
+            assert_eq!(contract.remove_sudo(CHAIN), Ok(()));
+            assert_eq!(contract.sudo(CHAIN), None);
+            assert_eq!(
+                contract.set_permissioned_authorities(CHAIN, members(&current)),
+                Err(FinalityError::PermissionDenied)
+            );
+        }
+
+        #[ink::test]
+        fn authorities_of_frozen_chains_are_not_updated() {
+            mock_signatures();
+            let mut contract = contract_with_chain(ConsensusEngine::Aleph);
+            let current = MockCommittee::new(1, 4);
+            let next = MockCommittee::new(2, 4);
+            contract
+                .set_permissioned_authorities(CHAIN, members(&current))
+                .unwrap();
+
+            let finalized = |state_root: Hash| {
+                let header = test_utils::header(7, Hash::zero(), state_root, Vec::new());
+                FinalizedHeader {
+                    justification: current.sign(&NativeBackend.hash_header(&header)),
+                    header,
+                }
+            };
+            let misbehaviour = Misbehaviour::detect(
+                finalized(Hash::repeat_byte(1)),
+                finalized(Hash::repeat_byte(2)),
+                &NativeBackend,
+            )
+            .unwrap();
+            contract.report_misbehaviour(CHAIN, misbehaviour).unwrap();
+
+            let proof = test_utils::session_proof(&current, 10, 1, &next.authorities());
+            assert_eq!(
+                contract.initialize_permissionless_authorities(CHAIN, 1, proof.clone()),
+                Err(FinalityError::ClientFrozen)
+            );
+            assert_eq!(
+                contract.update_authorities(CHAIN, proof),
+                Err(FinalityError::ClientFrozen)
+            );
+            assert_eq!(
+                contract
+                    .update_authorities([2; 32], test_utils::session_proof(&current, 10, 1, &[])),
+                Err(FinalityError::UnknownChain)
+            );
+        }
+
+        #[ink::test]
+        fn signers_of_the_previous_set_are_rejected_after_a_rotation() {
+            let previous = MockCommittee::new(1, 4);
+            let next = MockCommittee::new(2, 4);
+            let mut contract = contract_with_committee(&previous);
+            let header = test_utils::header(10, Hash::zero(), Hash::zero(), Vec::new());
+            assert_eq!(
+                contract.verify_consensus(finalized_proof(&previous, &header)),
+                Ok(())
+            );
+
+            // The commitment is recomputed from the new authorities
+            contract
+                .set_permissioned_authorities(CHAIN, members(&next))
+                .unwrap();
+            assert_eq!(
+                contract.authority_set(CHAIN),
+                Some(AuthoritySetRecord::new(members(&next)))
+            );
+            assert_ne!(
+                AuthoritySetRecord::new(members(&previous)),
+                AuthoritySetRecord::new(members(&next))
+            );
+
+            // Signers proven against the previous commitment are rejected
+            assert_eq!(
+                contract.verify_consensus(finalized_proof(&previous, &header)),
+                Err(ContractError::InvalidAuthorityProof)
+            );
+            // as well as a signer of the previous set claiming a position of the new one
+            let mut proof = finalized_proof(&next, &header);
+            proof.signers[0].authority = previous.authorities()[0].clone();
+            assert_eq!(
+                contract.verify_consensus(proof),
+                Err(ContractError::InvalidAuthorityProof)
+            );
+            assert_eq!(
+                contract.verify_consensus(finalized_proof(&next, &header)),
+                Ok(())
+            );
+        }
+
+        #[ink::test]
+        fn imported_headers_verify_state_proofs() {
+            let committee = MockCommittee::new(1, 4);
+            let mut contract = contract_with_committee(&committee);
+            let state = MockState::new(1_000, [(b"key".to_vec(), b"value".to_vec())]);
+
+            let record = import(&mut contract, &committee, &state, 10).unwrap();
+            assert_eq!(record.height, 10);
+            assert_eq!(record.state_root, state.state_root().0);
+            assert_eq!(record.timestamp, 1_000);
+            assert_eq!(contract.latest_checkpoint(CHAIN), Some(record));
+
+            assert_eq!(
+                contract.verify_state(state_proof(&state, b"key", 10)),
+                Ok(b"value".to_vec())
+            );
+
+            // Only under the state root of a stored header
+            assert_eq!(
+                contract.verify_state(state_proof(&state, b"key", 11)),
+                Err(ContractError::UnverifiedStateRoot)
+            );
+            let other = MockState::new(1_000, [(b"key".to_vec(), b"other".to_vec())]);
+            assert_eq!(
+                contract.verify_state(state_proof(&other, b"key", 10)),
+                Err(ContractError::UnverifiedStateRoot)
+            );
+        }
+
+        #[ink::test]
+        fn finalized_state_proofs_are_verified_along_with_the_block() {
+            let committee = MockCommittee::new(1, 4);
+            let contract = contract_with_committee(&committee);
+            let entries = [
+                (b"key".to_vec(), b"value".to_vec()),
+                (b"other".to_vec(), b"value".to_vec()),
+            ];
+            let state = MockState::new(1_000, entries);
+            let header = test_utils::header(10, Hash::zero(), state.state_root(), Vec::new());
+            let proof = finalized_proof(&committee, &header);
+
+            let (block, value) = contract
+                .verify_consensus_and_state(
+                    proof.clone(),
+                    timestamp_proof(&state, 10),
+                    state_proof(&state, b"key", 10),
+                    b"key".to_vec(),
+                )
+                .unwrap();
+            assert_eq!(block.height, 10);
+            assert_eq!(block.timestamp, 1_000);
+            assert_eq!(value, b"value".to_vec());
+
+            // The state proof must be under the state root of the finalized header
+            let other = MockState::new(1_000, [(b"key".to_vec(), b"other".to_vec())]);
+            assert_eq!(
+                contract.verify_consensus_and_state(
+                    proof.clone(),
+                    timestamp_proof(&state, 10),
+                    state_proof(&other, b"key", 10),
+                    b"key".to_vec(),
+                ),
+                Err(ContractError::StateRootMismatch)
+            );
+            // and about the given key only
+            assert_eq!(
+                contract.verify_consensus_and_state(
+                    proof.clone(),
+                    timestamp_proof(&state, 10),
+                    state_proof(&state, b"other", 10),
+                    b"key".to_vec(),
+                ),
+                Err(ContractError::UnexpectedStorageKey)
+            );
+            let mut timestamp = timestamp_proof(&state, 10);
+            timestamp.chain = [2; 32];
+            assert_eq!(
+                contract.verify_consensus_and_state(
+                    proof.clone(),
+                    timestamp,
+                    state_proof(&state, b"key", 10),
+                    b"key".to_vec(),
+                ),
+                Err(ContractError::ChainMismatch)
+            );
+
+            let handler = contract.consensus_handler(proof).unwrap();
+            assert_eq!(
+                handler.verify_state(&state_proof(&state, b"key", 10), b"key"),
+                Ok(b"value".to_vec())
+            );
+            assert_eq!(
+                handler.verify_state(&state_proof(&other, b"key", 10), b"key"),
+                Err(ContractError::StateRootMismatch)
+            );
+            assert_eq!(
+                handler.verify_state(&state_proof(&state, b"other", 10), b"key"),
+                Err(ContractError::UnexpectedStorageKey)
+            );
+            let both = StateTrieResponseProof::new(
+                CHAIN,
+                state.read_proof(&[b"key".to_vec(), b"other".to_vec()], 10),
+            )
+            .unwrap();
+            assert_eq!(
+                handler.verify_state(&both, b"key"),
+                Err(ContractError::UnexpectedStorageKey)
+            );
+        }
+
+        #[ink::test]
+        fn timestamps_of_other_chains_are_rejected() {
+            let committee = MockCommittee::new(1, 4);
+            let mut contract = contract_with_committee(&committee);
+            let state = MockState::new(1_000, Vec::new());
+            let header = test_utils::header(10, Hash::zero(), state.state_root(), Vec::new());
+            let proof = finalized_proof(&committee, &header);
+            let mut timestamp = timestamp_proof(&state, 10);
+            timestamp.chain = [2; 32];
+
+            assert_eq!(
+                contract.verify_block(proof.clone(), timestamp.clone()),
+                Err(ContractError::ChainMismatch)
+            );
+            assert_eq!(
+                contract.import_block(proof.clone(), timestamp),
+                Err(ContractError::ChainMismatch)
+            );
+            assert_eq!(contract.latest_checkpoint(CHAIN), None);
+
+            let block = contract
+                .verify_block(proof, timestamp_proof(&state, 10))
+                .unwrap();
+            assert_eq!(block.timestamp, 1_000);
+        }
+
+        #[ink::test]
+        fn conflicting_headers_are_rejected() {
+            let committee = MockCommittee::new(1, 4);
+            let mut contract = contract_with_committee(&committee);
+            let state = MockState::new(1_000, Vec::new());
+            let record = import(&mut contract, &committee, &state, 10).unwrap();
+
+            let conflicting = MockState::new(2_000, Vec::new());
+            assert_eq!(
+                import(&mut contract, &committee, &conflicting, 10),
+                Err(ContractError::ConflictingHeader)
+            );
+            assert_eq!(contract.header(CHAIN, 10), Some(record));
+
+            // The same header can be imported again
+            assert_eq!(import(&mut contract, &committee, &state, 10), Ok(record));
+        }
+
+        #[ink::test]
+        fn oldest_headers_are_pruned_first() {
+            let committee = MockCommittee::new(1, 4);
+            let mut contract = contract_with_committee(&committee);
+
+            assert_eq!(
+                contract.set_header_retention(2),
+                Err(FinalityError::PermissionDenied)
+            );
+            set_caller(accounts().alice);
+            assert_eq!(
+                contract.set_header_retention(0),
+                Err(FinalityError::InvalidRetention)
+            );
+            assert_eq!(contract.set_header_retention(2), Ok(()));
+
+            for height in [12, 10, 11] {
+                let state = MockState::new(u64::from(height), Vec::new());
+                import(&mut contract, &committee, &state, height).unwrap();
+            }
+            // Heights are kept in order, whatever the order of import
+            assert!(contract.header(CHAIN, 10).is_none());
+            assert!(contract.header(CHAIN, 11).is_some());
+            assert_eq!(contract.latest_checkpoint(CHAIN).unwrap().height, 12);
+
+            assert_eq!(contract.set_header_retention(1), Ok(()));
+            assert!(contract.header(CHAIN, 11).is_none());
+            assert_eq!(contract.latest_checkpoint(CHAIN).unwrap().height, 12);
+        }
+    }
 }
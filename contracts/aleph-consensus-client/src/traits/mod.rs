@@ -1,8 +1,17 @@
-use crate::{types::ConsensusContractResult, ConsensusProof, StateTrieResponseProof};
+use crate::{
+    types::{
+        AuthoritySetRecord, ChainId, ChainRecord, ConsensusContractResult, ConsensusEngine,
+        EmergencyPolicy, FinalityVersionsRecord, HeaderRecord, MisbehaviourRecord,
+    },
+    ConsensusProof, StateTrieResponseProof,
+};
 use alloc::vec::Vec;
 use core::fmt::Debug;
 use ink::primitives::AccountId;
-use light_client::{GetResponse, SessionIndex};
+use light_client::{
+    beefy::BeefyAuthoritySet, grandpa::crypto::AuthorityWeight, misbehaviour::Misbehaviour,
+    session::SessionProof, SessionIndex, VerifiedBlock,
+};
 use scale::{Decode, Encode};
 use utils::types::AuthorityId;
 
@@ -13,65 +22,207 @@ pub enum FinalityError {
     SudoAlreadyRemoved,
     InvalidProof,
     VerificationError,
+    /// Authorities are not rotated through state proofs yet
+    NotPermissionless,
+    /// The chain is not registered
+    UnknownChain,
+    ChainAlreadyRegistered,
+    /// The operation is not supported by the consensus engine of the chain
+    UnsupportedEngine,
+    /// Version changes are not in increasing session order, or the session period is missing
+    InvalidVersionSchedule,
+    /// The chain is frozen after a misbehaviour, its authorities are not updated
+    ClientFrozen,
+    /// At least one header of each chain must be kept
+    InvalidRetention,
+    /// The authority set is empty or has no weight, it could not finalize anything
+    InvalidAuthorities,
 }
 
 #[ink::trait_definition]
 pub trait FinalityGadget {
-    /// Initialize a permissionless setting for the consensus client, by rotating a committee of authorities
+    /// Registers a chain finalized by the given engine, along with its sudo account
+    #[ink(message)]
+    fn register_chain(
+        &mut self,
+        chain: ChainId,
+        engine: ConsensusEngine,
+        sudo: AuthorityId,
+    ) -> Result<(), FinalityError>;
+
+    /// Returns the ids of the registered chains
+    #[ink(message)]
+    fn chains(&self) -> Vec<ChainId>;
+
+    /// Returns the registry entry of the chain, if any
+    #[ink(message)]
+    fn chain(&self, chain: ChainId) -> Option<ChainRecord>;
+
+    /// Initialize a permissionless setting for an Aleph chain, by rotating a committee of authorities.
+    /// The proof of the next authorities must be under a block of the given session, finalized by the current authorities.
     #[ink(message)]
     fn initialize_permissionless_authorities(
         &mut self,
-        next_authorities: Vec<AuthorityId>,
+        chain: ChainId,
+        session: SessionIndex,
+        proof: SessionProof,
     ) -> Result<(), FinalityError>;
 
-    /// Set a permissioned set of authorities for the consensus client
+    /// Set a permissioned set of authorities for an Aleph chain
     #[ink(message)]
     fn set_permissioned_authorities(
         &mut self,
+        chain: ChainId,
         authorities: Vec<AuthorityId>,
     ) -> Result<(), FinalityError>;
 
-    /// Updates the list of authorities based on next authorities already stored and stores the new next authorities within a proof.
+    /// Set the GRANDPA authority set of a chain, it then follows the changes signaled by imported headers
+    #[ink(message)]
+    fn set_grandpa_authorities(
+        &mut self,
+        chain: ChainId,
+        set_id: u64,
+        authorities: Vec<(AuthorityId, AuthorityWeight)>,
+    ) -> Result<(), FinalityError>;
+
+    /// Set the current and next BEEFY validator sets of a chain, they then follow the changes of imported commitments
+    #[ink(message)]
+    fn set_beefy_authorities(
+        &mut self,
+        chain: ChainId,
+        current: BeefyAuthoritySet,
+        next: BeefyAuthoritySet,
+    ) -> Result<(), FinalityError>;
+
+    /// Updates the list of authorities of an Aleph chain based on next authorities already stored and stores the new next authorities within a proof.
+    /// Anyone can submit the proof, since it is verified against the trusted authorities.
     #[ink(message)]
     fn update_authorities(
         &mut self,
-        next_authorities: Vec<AuthorityId>,
-        proof: GetResponse,
+        chain: ChainId,
+        proof: SessionProof,
     ) -> Result<(), FinalityError>;
 
-    /// Set the next emergency finalizer account (aka sudo)
+    /// Set the next emergency finalizer account (aka sudo) of a chain
     #[ink(message)]
     fn update_emergency_finalizer_account(
         &mut self,
+        chain: ChainId,
         emergency_finalizer: AuthorityId,
     ) -> Result<(), FinalityError>;
 
-    /// Returns the current session, if necessary.
+    /// Returns the current session of an Aleph chain, if necessary.
     #[ink(message)]
-    fn current_session(&self) -> Option<SessionIndex>;
+    fn current_session(&self, chain: ChainId) -> Option<SessionIndex>;
 
-    /// Returns the current authorities.
+    /// Returns the current authorities of a chain.
     #[ink(message)]
-    fn authorities(&self) -> Option<Vec<AccountId>>;
+    fn authorities(&self, chain: ChainId) -> Option<Vec<AccountId>>;
+
+    /// Returns the commitment to the current authorities of an Aleph chain, signers of consensus proofs are proven against it
+    #[ink(message)]
+    fn authority_set(&self, chain: ChainId) -> Option<AuthoritySetRecord>;
 
     /// Checks whether the chain is in a permissionless setting
     #[ink(message)]
-    fn is_permissionless(&self) -> bool;
+    fn is_permissionless(&self, chain: ChainId) -> bool;
+
+    /// Sets the policy on blocks of a chain finalized by the emergency finalizer
+    #[ink(message)]
+    fn set_emergency_policy(
+        &mut self,
+        chain: ChainId,
+        policy: EmergencyPolicy,
+    ) -> Result<(), FinalityError>;
+
+    /// Returns the policy on blocks of a chain finalized by the emergency finalizer
+    #[ink(message)]
+    fn emergency_policy(&self, chain: ChainId) -> Option<EmergencyPolicy>;
+
+    /// Sets the schedule of the finality versions of an Aleph chain.
+    /// From the session of a version change on, justifications must be encoded with exactly the incoming version.
+    #[ink(message)]
+    fn set_finality_versions(
+        &mut self,
+        chain: ChainId,
+        versions: FinalityVersionsRecord,
+    ) -> Result<(), FinalityError>;
 
-    /// Retuns current sudo account. Returns `None` if there isnt't
+    /// Returns the schedule of the finality versions of a chain
     #[ink(message)]
-    fn sudo(&self) -> Option<AuthorityId>;
+    fn finality_versions(&self, chain: ChainId) -> Option<FinalityVersionsRecord>;
 
-    /// Removes sudo account, if some.
+    /// Retuns current sudo account of a chain. Returns `None` if there isnt't
     #[ink(message)]
-    fn remove_sudo(&mut self) -> Result<(), FinalityError>;
+    fn sudo(&self, chain: ChainId) -> Option<AuthorityId>;
+
+    /// Removes the sudo account of a chain, if some.
+    #[ink(message)]
+    fn remove_sudo(&mut self, chain: ChainId) -> Result<(), FinalityError>;
+
+    /// Verifies evidence of two conflicting headers finalized by the authorities of an Aleph chain, and freezes the chain
+    #[ink(message)]
+    fn report_misbehaviour(
+        &mut self,
+        chain: ChainId,
+        misbehaviour: Misbehaviour,
+    ) -> ConsensusContractResult<()>;
+
+    /// Returns the conflicting headers that froze the chain, if any
+    #[ink(message)]
+    fn frozen(&self, chain: ChainId) -> Option<MisbehaviourRecord>;
+
+    /// Unfreezes a chain, authorities are expected to be reviewed beforehand
+    #[ink(message)]
+    fn unfreeze(&mut self, chain: ChainId) -> Result<(), FinalityError>;
 
     #[ink(message)]
     fn verify_consensus(&self, request: ConsensusProof) -> ConsensusContractResult<()>;
+
+    /// Verifies a consensus proof and the timestamp proven at the finalized block.
+    /// Returns the verified height, timestamp and state root.
+    #[ink(message)]
+    fn verify_block(
+        &self,
+        request: ConsensusProof,
+        timestamp: StateTrieResponseProof,
+    ) -> ConsensusContractResult<VerifiedBlock>;
+
+    /// Verifies a consensus proof, the timestamp and a state proof under the state root of the finalized header, all at once.
+    /// The state proof must be about the given storage key, the verified block and the value are returned.
+    #[ink(message)]
+    fn verify_consensus_and_state(
+        &self,
+        request: ConsensusProof,
+        timestamp: StateTrieResponseProof,
+        state: StateTrieResponseProof,
+        key: Vec<u8>,
+    ) -> ConsensusContractResult<(VerifiedBlock, Vec<u8>)>;
+
+    /// Verifies a consensus proof along with the timestamp of the finalized block, and stores the verified header
+    #[ink(message)]
+    fn import_block(
+        &mut self,
+        request: ConsensusProof,
+        timestamp: StateTrieResponseProof,
+    ) -> ConsensusContractResult<HeaderRecord>;
+
+    /// Returns the stored header of a chain with the highest block number, if any
+    #[ink(message)]
+    fn latest_checkpoint(&self, chain: ChainId) -> Option<HeaderRecord>;
+
+    /// Returns the stored header of a chain at the given block number, if any
+    #[ink(message)]
+    fn header(&self, chain: ChainId, height: u32) -> Option<HeaderRecord>;
+
+    /// Sets the maximum number of stored headers of each chain, the oldest ones are pruned if needed
+    #[ink(message)]
+    fn set_header_retention(&mut self, retention: u32) -> Result<(), FinalityError>;
 }
 
 #[ink::trait_definition]
 pub trait StateTrieManager {
+    /// Verifies a state proof, under the state root of a stored verified header of its chain
     #[ink(message)]
     fn verify_state(&self, request: StateTrieResponseProof) -> ConsensusContractResult<Vec<u8>>;
 }
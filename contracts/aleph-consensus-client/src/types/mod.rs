@@ -1,11 +1,28 @@
 pub use self::Result as ConsensusContractResult;
 use alloc::vec::Vec;
 use core::fmt::Debug;
-use ink::storage::Mapping;
 use light_client::{
-    consensus::{AlephConsensusLogBuilder, AlephLogs, PhatContractConsensusClient},
-    finality::{crypto::AuthorityId as AuthorityPublic, justification},
-    ConsensusClient, GetResponse, GetSingleState,
+    authority_set::{Authorities, AuthorityProof, AuthoritySetCommitment},
+    backend::{HeaderHasher, PinkBackend},
+    beefy::{
+        mmr::{MmrLeaf, MmrLeafProof},
+        BeefyAuthoritySet, BeefyConsensusClient, BeefyProof, SignedCommitment,
+    },
+    consensus::{
+        self as light_consensus, AlephConsensusClient, EmergencyConfirmation,
+        EmergencyFinalityPolicy, Finality,
+    },
+    finality::crypto::AuthorityId as AuthorityPublic,
+    grandpa::{
+        crypto::{AuthorityId as GrandpaAuthorityPublic, AuthorityWeight},
+        justification::GrandpaJustification,
+        GrandpaAuthoritySet, GrandpaConsensusClient,
+    },
+    misbehaviour::{Equivocation, Misbehaviour},
+    session::{SessionAuthorities, SessionProof},
+    version::FinalityVersionSchedule,
+    ConsensusError, FinalityVersion, GetResponse, GetSingleState, GetTimestampResponseProof,
+    Header, SessionIndex, VerifiedBlock, VersionChange,
 };
 use scale::{Decode, Encode};
 use tispark_primitives::state_proofs::GetResponseProof;
@@ -14,6 +31,9 @@ use utils::types::AuthorityId;
 /// Type alias for the contract's result type.
 pub type Result<T> = core::result::Result<T, ContractError>;
 
+/// Genesis hash of a chain, it identifies the chain within the registry
+pub type ChainId = [u8; 32];
+
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ContractError {
@@ -21,87 +41,725 @@ pub enum ContractError {
     ConsensusClientInvalidLogs,
     ConsensusClientInvalidJustifications,
     ConsensusClientInvalidSignatures,
+    ConsensusClientNotEnoughSignatures,
     ConsensusClientInvalidEmergencySignature,
-    UntrustedAuthoritiesError,
+    /// A signer is not at the claimed position of the committed authority set
+    InvalidAuthorityProof,
     InvalidKeysError,
     CommitmentStateError,
     ConsensusClientInvalidStateProof,
+    InvalidTimestampProof,
+    EmergencyFinalityRejected,
+    EmergencyFinalityUnconfirmed,
+    ClientFrozen,
+    InvalidMisbehaviour,
+    InvalidSessionProof,
+    /// The state proof is not under the state root of a stored verified header
+    UnverifiedStateRoot,
+    /// The state proof is not under the state root of the finalized header
+    StateRootMismatch,
+    /// The state proof is not about the expected storage key
+    UnexpectedStorageKey,
+    /// The chain is not registered
+    UnknownChain,
+    /// The operation is not supported by the consensus engine of the chain
+    UnsupportedEngine,
+    /// The proofs are not about the same chain
+    ChainMismatch,
+    /// The GRANDPA authority set is not stored or it cannot follow the changes of the header
+    InvalidGrandpaAuthorities,
+    /// Version changes are not in increasing session order, or the session period is missing
+    InvalidVersionSchedule,
+    /// The header is not above the last header imported for the chain
+    StaleHeader,
+    /// A scheduled GRANDPA authority set change must be enacted before later headers are accepted
+    PendingAuthoritySetChange,
+    /// The BEEFY validator sets are not stored or they cannot follow the changes of the commitment
+    InvalidBeefyAuthorities,
+    /// Another header of the chain is already stored at the same height
+    ConflictingHeader,
+}
+
+/// Consensus engine that finalizes the blocks of a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ConsensusEngine {
+    Aleph,
+    Grandpa,
+    Beefy,
+}
+
+/// A chain of the registry, as it is stored by the contract
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ChainRecord {
+    pub engine: ConsensusEngine,
+    /// Commitment to the current Aleph authorities, signers of consensus proofs are proven against it
+    pub authority_set: AuthoritySetRecord,
+    /// Sudo account of the chain, it is the emergency finalizer of Aleph chains as well
+    pub sudo: Option<AuthorityId>,
+    /// Policy on blocks finalized by the emergency finalizer
+    pub emergency_policy: EmergencyPolicy,
+    /// Conflicting finalized headers, nothing is verified for the chain while they are recorded
+    pub frozen: Option<MisbehaviourRecord>,
+    /// Finality versions of Aleph chains by session, justifications are decoded strictly by them
+    pub finality_versions: FinalityVersionsRecord,
+}
+
+impl ChainRecord {
+    pub fn new(engine: ConsensusEngine, sudo: AuthorityId) -> Self {
+        Self {
+            engine,
+            authority_set: Default::default(),
+            sudo: Some(sudo),
+            emergency_policy: Default::default(),
+            frozen: None,
+            finality_versions: Default::default(),
+        }
+    }
+
+    /// The emergency finalizer (aka sudo) along with the policy on its blocks.
+    /// Once the sudo account is removed emergency finality is rejected, so a placeholder key is used.
+    pub fn emergency_finality(&self) -> (AuthorityId, EmergencyPolicy) {
+        match self.sudo {
+            Some(sudo) => (sudo, self.emergency_policy.clone()),
+            None => (AuthorityId::from([0; 32]), EmergencyPolicy::Reject),
+        }
+    }
+}
+
+/// Aleph authorities of a chain, as they are stored by the contract
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ConsensusAuthorities {
+    pub authorities: Vec<AuthorityId>,
+    pub next_authorities: Option<Vec<AuthorityId>>,
+    /// Session of the authorities, it is only known once they are rotated through state proofs
+    pub session: Option<SessionIndex>,
+}
+
+/// Authorities of a chain. They are only read to verify GRANDPA and BEEFY justifications,
+/// to rotate Aleph authorities or to verify misbehaviours.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ChainAuthorities {
+    Aleph(ConsensusAuthorities),
+    /// SCALE encoded `GrandpaAuthoritySet`, it follows the changes signaled by imported headers
+    Grandpa(Vec<u8>),
+    /// SCALE encoded current and next `BeefyAuthoritySet`, they follow the changes of imported commitments
+    Beefy(Vec<u8>),
+}
+
+impl ChainAuthorities {
+    pub fn grandpa(authority_set: &GrandpaAuthoritySet) -> Self {
+        ChainAuthorities::Grandpa(authority_set.encode())
+    }
+
+    pub fn grandpa_set(&self) -> Result<GrandpaAuthoritySet> {
+        match self {
+            ChainAuthorities::Grandpa(encoded) => GrandpaAuthoritySet::decode(&mut &encoded[..])
+                .map_err(|_| ContractError::InvalidGrandpaAuthorities),
+            _ => Err(ContractError::UnsupportedEngine),
+        }
+    }
+
+    pub fn beefy(current: &BeefyAuthoritySet, next: &BeefyAuthoritySet) -> Self {
+        ChainAuthorities::Beefy((current, next).encode())
+    }
+
+    /// Current and next BEEFY validator sets
+    pub fn beefy_sets(&self) -> Result<(BeefyAuthoritySet, BeefyAuthoritySet)> {
+        match self {
+            ChainAuthorities::Beefy(encoded) => Decode::decode(&mut &encoded[..])
+                .map_err(|_| ContractError::InvalidBeefyAuthorities),
+            _ => Err(ContractError::UnsupportedEngine),
+        }
+    }
+
+    /// Accounts of the current authorities
+    pub fn accounts(&self) -> Vec<AuthorityId> {
+        match self {
+            ChainAuthorities::Aleph(consensus_auth) => consensus_auth.authorities.clone(),
+            ChainAuthorities::Grandpa(_) => self
+                .grandpa_set()
+                .map(|authority_set| {
+                    authority_set
+                        .authorities()
+                        .iter()
+                        .map(|(key, _)| {
+                            let raw: &[u8] = key.as_ref();
+                            AuthorityId::try_from(raw).expect("An authority key is 32 bytes long")
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            // Only the Merkle roots of the BEEFY validator sets are stored
+            ChainAuthorities::Beefy(_) => Vec::new(),
+        }
+    }
+}
+
+/// Builds a GRANDPA authority set out of the weighted accounts of the authorities
+pub fn grandpa_authority_set(
+    set_id: u64,
+    authorities: Vec<(AuthorityId, AuthorityWeight)>,
+) -> GrandpaAuthoritySet {
+    let authorities = authorities
+        .into_iter()
+        .map(|(key, weight)| {
+            let key = GrandpaAuthorityPublic::try_from(key.as_ref())
+                .expect("The set of authority keys is expected to be in a valid format");
+            (key, weight)
+        })
+        .collect();
+
+    GrandpaAuthoritySet::new(set_id, authorities)
+}
+
+/// Policy on blocks finalized by the emergency finalizer, as it is stored by the contract
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum EmergencyPolicy {
+    /// Emergency finality is never accepted
+    #[default]
+    Reject,
+    /// Emergency finality is accepted only for blocks within the given (inclusive) ranges
+    BlockRanges(Vec<(u32, u32)>),
+    /// Emergency finality is accepted once a later block is finalized by the committee
+    RequireCommitteeConfirmation,
+}
+
+impl From<EmergencyPolicy> for EmergencyFinalityPolicy {
+    fn from(policy: EmergencyPolicy) -> Self {
+        match policy {
+            EmergencyPolicy::Reject => EmergencyFinalityPolicy::Reject,
+            EmergencyPolicy::BlockRanges(ranges) => EmergencyFinalityPolicy::BlockRanges(ranges),
+            EmergencyPolicy::RequireCommitteeConfirmation => {
+                EmergencyFinalityPolicy::RequireCommitteeConfirmation
+            }
+        }
+    }
+}
+
+/// Schedule of the finality versions of a chain, as it is stored by the contract
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct FinalityVersionsRecord {
+    /// Number of blocks of a session
+    pub session_period: u32,
+    /// Sessions from which the incoming versions are active, in increasing order
+    pub changes: Vec<(SessionIndex, FinalityVersion)>,
+}
+
+impl TryFrom<FinalityVersionsRecord> for FinalityVersionSchedule {
+    type Error = ContractError;
+
+    fn try_from(record: FinalityVersionsRecord) -> Result<Self> {
+        let mut schedule = FinalityVersionSchedule::new(record.session_period);
+        for (session, incoming) in record.changes {
+            schedule
+                .schedule(VersionChange { incoming, session })
+                .map_err(|_| ContractError::InvalidVersionSchedule)?;
+        }
+
+        Ok(schedule)
+    }
+}
+
+/// Two conflicting headers finalized by the authorities, as it is stored by the contract
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct MisbehaviourRecord {
+    pub height: u32,
+    pub first: [u8; 32],
+    pub second: [u8; 32],
+}
+
+impl From<Equivocation> for MisbehaviourRecord {
+    fn from(equivocation: Equivocation) -> Self {
+        Self {
+            height: equivocation.height,
+            first: equivocation.first.0,
+            second: equivocation.second.0,
+        }
+    }
+}
+
+/// A header verified by the contract, as it is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct HeaderRecord {
+    pub height: u32,
+    pub hash: [u8; 32],
+    pub state_root: [u8; 32],
+    /// Timestamp in milliseconds
+    pub timestamp: u64,
+    pub emergency_finalized: bool,
+}
+
+impl HeaderRecord {
+    pub fn new(hash: light_client::Hash, block: VerifiedBlock) -> Self {
+        Self {
+            height: block.height,
+            hash: hash.0,
+            state_root: block.state_root.0,
+            timestamp: block.timestamp,
+            emergency_finalized: block.emergency_finalized,
+        }
+    }
+
+    /// Whether a state proof is under the state root of this header
+    pub fn is_root_of(&self, proof: &StateTrieResponseProof) -> bool {
+        let root = proof.proof.state_root();
+        proof.proof.height() == u64::from(self.height)
+            && root.state_root.0 == self.state_root
+            && root.timestamp == self.timestamp
+    }
+}
+
+/// Commitment to the authority set, as it is stored by the contract
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AuthoritySetRecord {
+    /// Merkle root of the authority keys, in their order
+    pub root: [u8; 32],
+    pub len: u32,
+}
+
+impl AuthoritySetRecord {
+    pub fn new(authorities: Vec<AuthorityId>) -> Self {
+        AuthoritySetCommitment::new(&authority_keys(authorities)).into()
+    }
+}
+
+impl From<AuthoritySetCommitment> for AuthoritySetRecord {
+    fn from(commitment: AuthoritySetCommitment) -> Self {
+        Self {
+            root: commitment.root.0,
+            len: commitment.len,
+        }
+    }
+}
+
+impl From<AuthoritySetRecord> for AuthoritySetCommitment {
+    fn from(record: AuthoritySetRecord) -> Self {
+        Self {
+            root: record.root.into(),
+            len: record.len,
+        }
+    }
+}
+
+/// Converts the authorities into a valid set of Authority Public keys
+fn authority_keys(authorities: Vec<AuthorityId>) -> Vec<AuthorityPublic> {
+    authorities
+        .into_iter()
+        .map(|key| {
+            AuthorityPublic::try_from(key.as_ref())
+                .expect("The set of authority keys is expected to be in a valid format")
+        })
+        .collect()
+}
+
+/// Converts Authority Public keys back into the accounts stored by the contract
+fn authority_accounts(keys: &[AuthorityPublic]) -> Vec<AuthorityId> {
+    keys.iter()
+        .map(|key| {
+            let raw: &[u8] = key.as_ref();
+            AuthorityId::try_from(raw).expect("An authority key is 32 bytes long")
+        })
+        .collect()
+}
+
+/// Builds a consensus client for the Phat Contract environment
+fn phat_client(
+    authorities: impl Into<Authorities>,
+    emergency_finalizer: AuthorityId,
+    versions: FinalityVersionSchedule,
+) -> AlephConsensusClient<PinkBackend> {
+    // Convert the emergency finalizer into a valid public key
+    let emergency_finalizer = AuthorityPublic::try_from(emergency_finalizer.as_ref())
+        .expect("The emergency finalizer is expected to be in a valid format");
+
+    AlephConsensusClient::with_backend(PinkBackend, authorities, emergency_finalizer)
+        .with_versions(versions)
+}
+
+/// Authorities of a session, as they are stored by the contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionRecord {
+    pub session: SessionIndex,
+    pub authorities: Vec<AuthorityId>,
+    pub next_authorities: Option<Vec<AuthorityId>>,
+}
+
+impl From<SessionAuthorities> for SessionRecord {
+    fn from(session: SessionAuthorities) -> Self {
+        Self {
+            session: session.session(),
+            authorities: authority_accounts(session.authorities()),
+            next_authorities: session.next_authorities().map(authority_accounts),
+        }
+    }
+}
+
+impl From<SessionRecord> for SessionAuthorities {
+    fn from(record: SessionRecord) -> Self {
+        let authorities =
+            SessionAuthorities::new(record.session, authority_keys(record.authorities));
+        match record.next_authorities {
+            Some(next_authorities) => {
+                authorities.with_next_authorities(authority_keys(next_authorities))
+            }
+            None => authorities,
+        }
+    }
+}
+
+/// Imports the next authorities proven under a block of the current session, finalized by the current authorities
+pub fn import_next_authorities(
+    session: SessionIndex,
+    authorities: Vec<AuthorityId>,
+    proof: SessionProof,
+) -> Result<SessionRecord> {
+    let mut session = SessionAuthorities::new(session, authority_keys(authorities));
+    session
+        .import_next_authorities(proof, &PinkBackend)
+        .map_err(|_| ContractError::InvalidSessionProof)?;
+
+    Ok(session.into())
+}
+
+/// Rotates the authorities if the proof is of a block of the next session, finalized by the next authorities.
+/// Otherwise the proof must be of a block of the current session, and only the next authorities are updated.
+pub fn update_session(record: SessionRecord, proof: SessionProof) -> Result<SessionRecord> {
+    let session = SessionAuthorities::from(record);
+
+    let mut rotated = session.clone();
+    if rotated.rotate(proof.clone(), &PinkBackend).is_ok() {
+        return Ok(rotated.into());
+    }
+
+    let mut imported = session;
+    imported
+        .import_next_authorities(proof, &PinkBackend)
+        .map_err(|_| ContractError::InvalidSessionProof)?;
+
+    Ok(imported.into())
+}
+
+/// Verifies evidence of two conflicting headers, both finalized by the given authorities
+pub fn verify_misbehaviour(
+    authorities: Vec<AuthorityId>,
+    emergency_finalizer: AuthorityId,
+    misbehaviour: &Misbehaviour,
+) -> Result<MisbehaviourRecord> {
+    // Justifications of misbehaviours are already decoded, so the finality versions are not needed
+    phat_client(
+        authority_keys(authorities),
+        emergency_finalizer,
+        Default::default(),
+    )
+    .verify_misbehaviour(misbehaviour)
+    .map(MisbehaviourRecord::from)
+    .map_err(|_| ContractError::InvalidMisbehaviour)
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConsensusState {
     pub block: u32,
     pub extrinsics_root: light_client::Hash,
     pub state_root: light_client::Hash,
     pub parent_hash: light_client::Hash,
-    pub logs: AlephLogs,
+    /// SCALE encoded header digest
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "tispark_primitives::serde_hex::bytes")
+    )]
+    pub digest: Vec<u8>,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConsensusProof {
+    /// The chain the block belongs to
+    pub chain: ChainId,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "tispark_primitives::serde_hex::bytes")
+    )]
     pub justification: Vec<u8>,
     pub state: ConsensusState,
-    pub untrusted_auth: Vec<AuthorityId>,
+    /// Authorities that signed the justification, along with their proofs in the committed authority set
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "tispark_primitives::serde_hex::scale_seq")
+    )]
+    pub signers: Vec<AuthorityProof>,
+    /// Needed if the block is finalized by the emergency finalizer and the policy requires a confirmation
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "tispark_primitives::serde_hex::scale")
+    )]
+    pub emergency_confirmation: Option<EmergencyConfirmation>,
+}
+
+/// Justification of a BEEFY chain, the header is the one of the consensus proof
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct BeefyJustification {
+    pub signed_commitment: SignedCommitment,
+    /// Leaf appended by the child of the header
+    pub leaf: MmrLeaf,
+    pub leaf_proof: MmrLeafProof,
+}
+
+/// Consensus client of the engine of a chain
+pub enum EngineClient {
+    Aleph(AlephConsensusClient<PinkBackend>),
+    Grandpa(GrandpaConsensusClient<PinkBackend>),
+    Beefy(BeefyConsensusClient<PinkBackend>),
 }
 
 #[derive(Default)]
 pub struct ConsensusNotInit;
-pub struct AuthoritiesSet(Vec<AuthorityId>, AuthorityId);
+pub struct AuthoritiesSet(EngineClient);
 pub struct ConsensusInitialized {
-    authorities: Vec<AuthorityId>,
-    emergency_finalizer: AuthorityId,
+    client: EngineClient,
+    emergency_policy: EmergencyFinalityPolicy,
     proof: ConsensusProof,
 }
 
 pub struct ConsensusProofClientHandler {
-    client: PhatContractConsensusClient,
+    client: EngineClient,
     proof: ConsensusProof,
 }
 
+fn finality_error(error: ConsensusError) -> ContractError {
+    match error {
+        ConsensusError::NotEnoughSignatures => ContractError::ConsensusClientNotEnoughSignatures,
+        ConsensusError::EmergencyFinalityRejected => ContractError::EmergencyFinalityRejected,
+        ConsensusError::EmergencyFinalityUnconfirmed => ContractError::EmergencyFinalityUnconfirmed,
+        ConsensusError::StaleHeader => ContractError::StaleHeader,
+        ConsensusError::PendingAuthoritySetChange => ContractError::PendingAuthoritySetChange,
+        _ => ContractError::ConsensusClientInvalidSignatures,
+    }
+}
+
 impl ConsensusProofClientHandler {
     pub fn verify_consensus_state(&self) -> Result<light_client::Hash> {
-        // Build the consensus state
-        let aura_pre_runtime = &self.proof.state.logs.aura_pre_runtime;
-        let seal = &self.proof.state.logs.seal;
-        let digest = AlephConsensusLogBuilder::logs(aura_pre_runtime, seal)
-            .map_err(|_| ContractError::ConsensusClientInvalidLogs)?
-            .build();
+        self.verify_finality()
+            .map(|(consensus_state, _)| consensus_state)
+    }
+
+    /// Builds the header the consensus proof is about
+    fn header(&self) -> Result<Header> {
+        let digest = light_consensus::decode_digest(&self.proof.state.digest)
+            .map_err(|_| ContractError::ConsensusClientInvalidLogs)?;
 
-        let consensus_state = self.client.build_consenus_state(
+        Ok(Header::new(
             self.proof.state.block,
             self.proof.state.extrinsics_root,
             self.proof.state.state_root,
             self.proof.state.parent_hash,
             digest,
-        );
-
-        // Decodes Aleph Justification
-        let justification =
-            justification::backwards_compatible_decode(self.proof.justification.clone())
-                .map_err(|_| ContractError::ConsensusClientInvalidJustifications)?;
-
-        match justification {
-            // Authority signature set verification
-            justification::AlephJustification::CommitteeMultisignature(signatures) => {
-                self.client
-                    .verify_consensus(consensus_state, signatures)
-                    .map_err(|_| ContractError::ConsensusClientInvalidSignatures)?;
+        ))
+    }
+
+    /// Verifies the consensus state and returns how the block has been finalized
+    fn verify_finality(&self) -> Result<(light_client::Hash, Finality)> {
+        // Build the consensus state
+        let consensus_state = PinkBackend.hash_header(&self.header()?);
+
+        let finality = match &self.client {
+            EngineClient::Aleph(client) => {
+                // Decodes Aleph Justification, according to the finality version of the block
+                let justification = client
+                    .decode_justification(self.proof.state.block, self.proof.justification.clone())
+                    .map_err(|_| ContractError::ConsensusClientInvalidJustifications)?;
+
+                // Verifies either the authority signature set or the emergency finalizer signature
+                client
+                    .verify_justification(
+                        self.proof.state.block,
+                        consensus_state,
+                        justification,
+                        self.proof.emergency_confirmation.clone(),
+                    )
+                    .map_err(finality_error)?
             }
+            EngineClient::Grandpa(client) => {
+                let justification =
+                    GrandpaJustification::decode(&mut &self.proof.justification[..])
+                        .map_err(|_| ContractError::ConsensusClientInvalidJustifications)?;
 
-            // Emergency finalizer signature verification
-            justification::AlephJustification::EmergencySignature(signature) => {
-                let verify = self
-                    .client
-                    .verify_consensus_sudo(consensus_state, signature);
-                if !verify {
-                    return Err(ContractError::ConsensusClientInvalidEmergencySignature);
-                }
+                // The voters are the ones at the height of the header
+                client
+                    .verify_header(&self.header()?, justification)
+                    .map(Finality::Committee)
+                    .map_err(finality_error)?
             }
-        }
+            EngineClient::Beefy(client) => {
+                // The header is proven through the MMR leaf, so it is the one the consensus state is about
+                client
+                    .verify_header(&self.beefy_proof()?)
+                    .map(|(_, report)| Finality::Committee(report))
+                    .map_err(finality_error)?
+            }
+        };
+
         // Returns the block hash that has been validated
-        Ok(consensus_state)
+        Ok((consensus_state, finality))
+    }
+
+    /// The GRANDPA authority set once the finalized header is imported, along with the changes the header signals.
+    /// Headers signaling changes must be imported, otherwise the stored set falls behind.
+    /// Returns `None` for other engines.
+    pub fn next_grandpa_set(&self) -> Result<Option<GrandpaAuthoritySet>> {
+        match &self.client {
+            EngineClient::Grandpa(client) => {
+                let mut authority_set = client.authority_set().clone();
+                authority_set
+                    .import_finalized_header(&self.header()?)
+                    .map_err(|error| match error {
+                        ConsensusError::StaleHeader | ConsensusError::PendingAuthoritySetChange => {
+                            finality_error(error)
+                        }
+                        _ => ContractError::InvalidGrandpaAuthorities,
+                    })?;
+
+                Ok(Some(authority_set))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The current and next BEEFY validator sets once the header is imported.
+    /// Returns `None` for other engines.
+    pub fn next_beefy_sets(&self) -> Result<Option<(BeefyAuthoritySet, BeefyAuthoritySet)>> {
+        match &self.client {
+            EngineClient::Beefy(client) => {
+                let mut client = BeefyConsensusClient::with_backend(
+                    PinkBackend,
+                    *client.current(),
+                    *client.next(),
+                );
+                client
+                    .import(&self.beefy_proof()?)
+                    .map_err(|_| ContractError::InvalidBeefyAuthorities)?;
+
+                Ok(Some((*client.current(), *client.next())))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Builds the BEEFY proof of the header the consensus proof is about
+    fn beefy_proof(&self) -> Result<BeefyProof> {
+        let justification = BeefyJustification::decode(&mut &self.proof.justification[..])
+            .map_err(|_| ContractError::ConsensusClientInvalidJustifications)?;
+
+        Ok(BeefyProof {
+            signed_commitment: justification.signed_commitment,
+            leaf: justification.leaf,
+            leaf_proof: justification.leaf_proof,
+            header: self.header()?,
+        })
+    }
+
+    /// Verifies the consensus state along with the timestamp stored at the finalized block
+    pub fn verify_block(&self, timestamp: &StateTrieResponseProof) -> Result<VerifiedBlock> {
+        self.verify_header(timestamp).map(|(_, block)| block)
+    }
+
+    /// Verifies the consensus state and a state proof under the state root of the finalized header.
+    /// The proof must be about the given storage key only, its value is returned.
+    pub fn verify_state(&self, state: &StateTrieResponseProof, key: &[u8]) -> Result<Vec<u8>> {
+        self.verify_finality()?;
+
+        self.finalized_state(state, key)
+    }
+
+    /// Verifies the consensus state along with the timestamp, and a state proof under the state root of the finalized header.
+    /// Finality is verified once for both proofs.
+    pub fn verify_block_and_state(
+        &self,
+        timestamp: &StateTrieResponseProof,
+        state: &StateTrieResponseProof,
+        key: &[u8],
+    ) -> Result<(VerifiedBlock, Vec<u8>)> {
+        let (_, block) = self.verify_header(timestamp)?;
+        let value = self.finalized_state(state, key)?;
+
+        Ok((block, value))
+    }
+
+    /// Verifies a state proof of the given key only, under the state root of the header the consensus proof is about
+    fn finalized_state(&self, state: &StateTrieResponseProof, key: &[u8]) -> Result<Vec<u8>> {
+        if state.chain != self.proof.chain {
+            return Err(ContractError::ChainMismatch);
+        }
+        if state.proof.state_root().state_root != self.proof.state.state_root {
+            return Err(ContractError::StateRootMismatch);
+        }
+        let keys = state.proof.keys();
+        if keys.len() != 1 || keys[0] != key {
+            return Err(ContractError::UnexpectedStorageKey);
+        }
+
+        state.verify_state()
+    }
+
+    /// Verifies the consensus state along with the timestamp, returning the hash of the finalized header as well
+    pub fn verify_header(
+        &self,
+        timestamp: &StateTrieResponseProof,
+    ) -> Result<(light_client::Hash, VerifiedBlock)> {
+        let (consensus_state, finality) = self.verify_finality()?;
+
+        // The state root is trusted since it is part of the finalized header
+        let block = GetTimestampResponseProof::new(timestamp.proof.clone())
+            .map_err(|_| ContractError::InvalidKeysError)?
+            .verify_block(self.proof.state.block, self.proof.state.state_root)
+            .map_err(|_| ContractError::InvalidTimestampProof)?;
+
+        Ok((
+            consensus_state,
+            VerifiedBlock {
+                emergency_finalized: finality.is_emergency(),
+                ..block
+            },
+        ))
     }
 }
 
@@ -118,43 +776,51 @@ impl Default for ConsensusProofHandlerBuilder<ConsensusNotInit> {
 }
 
 impl ConsensusProofHandlerBuilder<ConsensusNotInit> {
+    /// Sets up the client with the signers of the justification, proven against the commitment of the authority set.
+    /// Justifications are decoded by the finality versions of the chain.
     pub fn setup_client(
         self,
-        authorities: Vec<AuthorityId>,
-        untrusted_authorites: Vec<AuthorityId>,
+        commitment: AuthoritySetRecord,
+        signers: Vec<AuthorityProof>,
         emergency_finalizer: AuthorityId,
+        versions: FinalityVersionsRecord,
     ) -> Result<ConsensusProofHandlerBuilder<AuthoritiesSet>> {
-        if Self::verify_untrasted_authorities(&authorities, &untrusted_authorites) {
-            Ok(ConsensusProofHandlerBuilder {
-                state: AuthoritiesSet(untrusted_authorites, emergency_finalizer),
-            })
-        } else {
-            Err(ContractError::UntrustedAuthoritiesError)
+        let signers = AuthoritySetCommitment::from(commitment)
+            .verify_signers(signers)
+            .map_err(|_| ContractError::InvalidAuthorityProof)?;
+        let versions = FinalityVersionSchedule::try_from(versions)?;
+
+        Ok(ConsensusProofHandlerBuilder {
+            state: AuthoritiesSet(EngineClient::Aleph(phat_client(
+                signers,
+                emergency_finalizer,
+                versions,
+            ))),
+        })
+    }
+
+    /// Sets up a GRANDPA client, justifications carry the keys of their signers
+    pub fn setup_grandpa_client(
+        self,
+        authority_set: GrandpaAuthoritySet,
+    ) -> ConsensusProofHandlerBuilder<AuthoritiesSet> {
+        let client = GrandpaConsensusClient::with_backend(PinkBackend, authority_set);
+
+        ConsensusProofHandlerBuilder {
+            state: AuthoritiesSet(EngineClient::Grandpa(client)),
         }
     }
 
-    fn verify_untrasted_authorities(
-        authorities: &[AuthorityId],
-        untrusted_authorites: &[AuthorityId],
-    ) -> bool {
-        if authorities.len() != untrusted_authorites.len() {
-            return false;
-        } else {
-            let mut trusted: Mapping<AuthorityId, bool> = Mapping::new();
+    /// Sets up a BEEFY client, signers prove their membership against the Merkle roots of the validator sets
+    pub fn setup_beefy_client(
+        self,
+        current: BeefyAuthoritySet,
+        next: BeefyAuthoritySet,
+    ) -> ConsensusProofHandlerBuilder<AuthoritiesSet> {
+        let client = BeefyConsensusClient::with_backend(PinkBackend, current, next);
 
-            // add each trusted authorities in the map as still not found trusted authorities
-            for authority in authorities {
-                trusted.insert(authority, &true);
-            }
-            // verify if each untrasted authority is trusted
-            for untrusted in untrusted_authorites {
-                let is_trusted = trusted.get(untrusted).is_some_and(|is_trusted| is_trusted);
-                if !is_trusted {
-                    return false;
-                }
-            }
-            // All untrasted authorities have passed the test
-            return true;
+        ConsensusProofHandlerBuilder {
+            state: AuthoritiesSet(EngineClient::Beefy(client)),
         }
     }
 }
@@ -164,12 +830,10 @@ impl ConsensusProofHandlerBuilder<AuthoritiesSet> {
         self,
         proof: ConsensusProof,
     ) -> ConsensusProofHandlerBuilder<ConsensusInitialized> {
-        let authorities = self.state.0;
-        let emergency_finalizer = self.state.1;
         ConsensusProofHandlerBuilder {
             state: ConsensusInitialized {
-                authorities,
-                emergency_finalizer,
+                client: self.state.0,
+                emergency_policy: Default::default(),
                 proof,
             },
         }
@@ -177,22 +841,20 @@ impl ConsensusProofHandlerBuilder<AuthoritiesSet> {
 }
 
 impl ConsensusProofHandlerBuilder<ConsensusInitialized> {
+    /// Sets the emergency finality policy, by default emergency finality is rejected.
+    /// It only applies to Aleph chains.
+    pub fn emergency_policy(mut self, emergency_policy: EmergencyFinalityPolicy) -> Self {
+        self.state.emergency_policy = emergency_policy;
+        self
+    }
+
     pub fn build(self) -> ConsensusProofClientHandler {
-        // Convert the authorities into a valid set of Authority Public keys
-        let mut authorities = Vec::new();
-        self.state.authorities.into_iter().for_each(|key| {
-            authorities.push(
-                AuthorityPublic::try_from(key.as_ref())
-                    .expect("The set of authority keys is expected to be in a valid format"),
-            )
-        });
-
-        // Convert the emergency finalizer into a valid public key
-        let emergency_finalizer =
-            AuthorityPublic::try_from(self.state.emergency_finalizer.as_ref())
-                .expect("The emergency finalizer is expected to be in a valid format");
-
-        let client = PhatContractConsensusClient::new(authorities, emergency_finalizer);
+        let client = match self.state.client {
+            EngineClient::Aleph(client) => {
+                EngineClient::Aleph(client.with_emergency_policy(self.state.emergency_policy))
+            }
+            client => client,
+        };
         let proof = self.state.proof;
 
         ConsensusProofClientHandler { client, proof }
@@ -202,7 +864,10 @@ impl ConsensusProofHandlerBuilder<ConsensusInitialized> {
 // state verification feature
 #[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StateTrieResponseProof {
+    /// The chain the state belongs to
+    pub chain: ChainId,
     pub proof: GetResponseProof,
 }
 
@@ -211,8 +876,8 @@ impl StateTrieResponseProof {
         GetResponse(self.proof.clone())
     }
 
-    pub fn new(proof: GetResponseProof) -> Result<Self> {
-        let commitment_response = StateTrieResponseProof { proof };
+    pub fn new(chain: ChainId, proof: GetResponseProof) -> Result<Self> {
+        let commitment_response = StateTrieResponseProof { chain, proof };
         if commitment_response.response().verify_key_uniquness() {
             Ok(commitment_response)
         } else {
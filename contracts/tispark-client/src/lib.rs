@@ -15,8 +15,7 @@ use pink_extension as pink;
 mod tispark_client {
     use super::pink;
     use crate::{
-        consensus,
-        state::{self, CommitmentStateDecoder},
+        state::{self, CommitmentStateDecoder, ResultCommitment},
         traits::CommitRevealContractManager,
         types::{
             commitment::{ContractCommitment, ContractCommitmentBuilder},
@@ -26,15 +25,19 @@ mod tispark_client {
             },
             ContractError, ContractResult, VersionNumber, Versioned,
         },
-        ContractServiceId, ServiceId,
+        ContractServiceId, ServiceId, ServiceRecord,
     };
+    use aleph_consensus_client::{ChainId, ConsensusEngine};
     use alloc::{string::String, vec::Vec};
-    use ink::storage::{Lazy, Mapping};
-    use light_client::Hash as H256;
+    use ink::{
+        storage::{Lazy, Mapping},
+        ToAccountId,
+    };
+    use light_client::{state::commitment_entry, Hash as H256, VerifiedBlock};
     use pink::PinkEnvironment;
     use scale::Encode;
     use tispark_primitives::commit_reveal::{CommitRevealManager, DecryptedData, QueryMetadata};
-    use tispark_rpc::TiSparkRpcRef;
+    use tispark_rpc::{RevealResultRequest, TiSparkRpcRef};
     use utils::{
         types::{
             AccessControl, AuthorityId, ContractId, CryptoHasher, Hash as CodeHash, Random,
@@ -53,10 +56,68 @@ mod tispark_client {
         consensus_client: ConsensusClientRef,
         /// Sudo account for emergency operations
         sudo: SudoAccount,
-        /// Registered services
-        services: Mapping<ServiceId, ContractServiceId>,
-        /// Rpc contract
-        rpc: TiSparkRpcRef,
+        /// Registered services, each one is bound to a chain
+        services: Mapping<ServiceId, ServiceRecord>,
+        /// Rpc contract of each chain, by chain id (genesis hash)
+        rpcs: Mapping<ChainId, TiSparkRpcRef>,
+    }
+
+    /// An rpc contract has been instantiated for a chain
+    #[ink(event)]
+    pub struct ChainRegistered {
+        #[ink(topic)]
+        chain: ChainId,
+        engine: ConsensusEngine,
+        rpc: AccountId,
+    }
+
+    /// A service has been registered, bound to the given chain
+    #[ink(event)]
+    pub struct ServiceRegistered {
+        #[ink(topic)]
+        service: ServiceId,
+        contract: ContractServiceId,
+        chain: ChainId,
+    }
+
+    /// The contract of a service has been replaced, the service stays bound to its chain
+    #[ink(event)]
+    pub struct ServiceReassigned {
+        #[ink(topic)]
+        service: ServiceId,
+        previous: ContractServiceId,
+        contract: ContractServiceId,
+    }
+
+    /// The keyring material has been rotated, commitments of previous versions can no longer be revealed
+    #[ink(event)]
+    pub struct KeyringMaterialUpdated {
+        #[ink(topic)]
+        signing_version: VersionNumber,
+        #[ink(topic)]
+        commitment_version: VersionNumber,
+    }
+
+    /// A commitment has been proven under a finalized block of the chain of the service, see `record_commit`
+    #[ink(event)]
+    pub struct Committed {
+        #[ink(topic)]
+        service: ServiceId,
+        #[ink(topic)]
+        commit_id: H256,
+        chain: ChainId,
+        block: VerifiedBlock,
+    }
+
+    /// A revealed commitment has been proven under a finalized block of the chain of the service, see `record_reveal`
+    #[ink(event)]
+    pub struct Revealed {
+        #[ink(topic)]
+        service: ServiceId,
+        #[ink(topic)]
+        commit_id: H256,
+        chain: ChainId,
+        block: VerifiedBlock,
     }
 
     #[derive(Encode)]
@@ -94,6 +155,8 @@ mod tispark_client {
         #[ink(constructor)]
         pub fn new(
             consensus_client_id: ContractId,
+            chain: ChainId,
+            engine: ConsensusEngine,
             rpc_contract_code_hash: CodeHash,
             http_endpoint: String,
         ) -> Self {
@@ -120,12 +183,15 @@ mod tispark_client {
             let sudo = SudoAccount::new(Some(sudo));
 
             let services = Mapping::new();
-            // Rpc contract instantiation
-            let rpc = TiSparkRpcRef::new(http_endpoint)
-                .code_hash(rpc_contract_code_hash)
-                .endowment(0)
-                .salt_bytes([&pub_key[..], &sudo.to_vec()[..]].concat())
-                .instantiate();
+            // Rpc contract instantiation for the first chain
+            let mut rpcs = Mapping::new();
+            let rpc = Self::instantiate_rpc(
+                rpc_contract_code_hash,
+                http_endpoint,
+                engine,
+                [&pub_key[..], &sudo.to_vec()[..], &chain[..]].concat(),
+            );
+            rpcs.insert(chain, &rpc);
 
             Self {
                 sign_material,
@@ -133,10 +199,23 @@ mod tispark_client {
                 consensus_client: ConsensusClientRef::new(consensus_client_id),
                 sudo,
                 services,
-                rpc,
+                rpcs,
             }
         }
 
+        fn instantiate_rpc(
+            code_hash: CodeHash,
+            http_endpoint: String,
+            engine: ConsensusEngine,
+            salt: Vec<u8>,
+        ) -> TiSparkRpcRef {
+            TiSparkRpcRef::new(http_endpoint, engine)
+                .code_hash(code_hash)
+                .endowment(0)
+                .salt_bytes(salt)
+                .instantiate()
+        }
+
         fn signing_material(&self) -> SigningMaterial {
             self.sign_material
                 .get()
@@ -156,19 +235,64 @@ mod tispark_client {
 
         #[ink(message)]
         pub fn contract_service(&self, id: ServiceId) -> Option<ContractServiceId> {
-            self.services.get(id)
+            self.services.get(id).map(|service| service.contract)
+        }
+
+        /// Returns the chain the service is bound to
+        #[ink(message)]
+        pub fn service_chain(&self, id: ServiceId) -> Option<ChainId> {
+            self.services.get(id).map(|service| service.chain)
+        }
+
+        /// Registers a chain by instantiating an rpc contract for its node.
+        /// The chain must be registered in the consensus client as well.
+        #[ink(message)]
+        pub fn register_chain(
+            &mut self,
+            chain: ChainId,
+            engine: ConsensusEngine,
+            rpc_contract_code_hash: CodeHash,
+            http_endpoint: String,
+        ) -> ContractResult<()> {
+            self.ensure_owner()?;
+            if self.rpcs.contains(chain) {
+                return Err(ContractError::ChainAlreadyRegistered);
+            }
+
+            let salt = [&self.pubkey()[..], &self.sudo.to_vec()[..], &chain[..]].concat();
+            let rpc = Self::instantiate_rpc(rpc_contract_code_hash, http_endpoint, engine, salt);
+            self.rpcs.insert(chain, &rpc);
+
+            self.env().emit_event(ChainRegistered {
+                chain,
+                engine,
+                rpc: rpc.to_account_id(),
+            });
+            Ok(())
         }
 
+        /// Registers a service, its commitments are revealed through state proofs of the given chain
         #[ink(message)]
         pub fn register_service(
             &mut self,
             service: ServiceId,
             contract: ContractServiceId,
+            chain: ChainId,
         ) -> ContractResult<()> {
             self.ensure_owner()?;
+            if !self.rpcs.contains(chain) {
+                return Err(ContractError::UnknownChain);
+            }
 
             if self.ensure_service_exists(&service).is_err() {
-                self.services.insert(service, &contract);
+                self.services
+                    .insert(service, &ServiceRecord { contract, chain });
+
+                self.env().emit_event(ServiceRegistered {
+                    service,
+                    contract,
+                    chain,
+                });
                 Ok(())
             } else {
                 Err(ContractError::ServiceAlreadyExists)
@@ -183,8 +307,16 @@ mod tispark_client {
         ) -> ContractResult<()> {
             self.ensure_owner()?;
 
-            if self.ensure_service_exists(&service).is_ok() {
-                self.services.insert(service, &contract);
+            // The service stays bound to its chain
+            if let Some(record) = self.services.get(service) {
+                self.services
+                    .insert(service, &ServiceRecord { contract, ..record });
+
+                self.env().emit_event(ServiceReassigned {
+                    service,
+                    previous: record.contract,
+                    contract,
+                });
                 Ok(())
             } else {
                 Err(ContractError::InvalidService)
@@ -198,22 +330,52 @@ mod tispark_client {
                 .map_err(|_| ContractError::BadOrigin)
         }
 
-        fn ensure_service_contract(&self, service: &ServiceId) -> ContractResult<()> {
-            let contract = self.ensure_service_exists(service)?;
+        /// Returns the service if the caller is its contract
+        fn ensure_service_contract(&self, service: &ServiceId) -> ContractResult<ServiceRecord> {
+            let record = self.ensure_service_exists(service)?;
 
-            AccessControl::new(Some(contract))
+            AccessControl::new(Some(record.contract))
                 .caller(pink::env().caller())
                 .verify()
-                .map_err(|_| ContractError::BadOrigin)
+                .map_err(|_| ContractError::BadOrigin)?;
+            Ok(record)
         }
 
-        fn ensure_service_exists(&self, service: &ServiceId) -> ContractResult<ContractServiceId> {
+        fn ensure_service_exists(&self, service: &ServiceId) -> ContractResult<ServiceRecord> {
             if let Some(id) = self.services.get(service) {
                 Ok(id)
             } else {
                 Err(ContractError::InvalidService)
             }
         }
+
+        /// Verifies the proofs of a commitment of the service, under a finalized block of its chain.
+        /// Returns the verified block along with the commitment stored by the chain.
+        fn verify_commitment(
+            &self,
+            service: &ServiceRecord,
+            commit_id: &H256,
+            proofs: &RevealResultRequest,
+        ) -> ContractResult<(VerifiedBlock, ResultCommitment)> {
+            // The proofs must be about the chain of the service
+            if proofs.proof().chain != service.chain {
+                return Err(ContractError::ChainMismatch);
+            }
+
+            // Verify the consensus proof along with the timestamp of the finalized block, and the (key, value) pair
+            // of the commitment within a state proof under the state root of the finalized header
+            let storage_key = commitment_entry(commit_id).key().to_vec();
+            let (block, res) = state::verify_consensus_and_state(
+                &self.consensus_client,
+                proofs.proof(),
+                proofs.timestamp(),
+                proofs.response(),
+                storage_key,
+            )
+            .map_err(ContractError::ConsensusClient)?;
+
+            Ok((block, CommitmentStateDecoder::decode(res)?))
+        }
     }
 
     impl CommitRevealContractManager for TiSparkClient {
@@ -225,6 +387,11 @@ mod tispark_client {
             let (new_signing, secret_key, pub_key) = self.signing_material().version.increment();
             let (new_commit_key, key, _) = self.commitment_key().version.increment();
 
+            self.env().emit_event(KeyringMaterialUpdated {
+                signing_version: new_signing.number(),
+                commitment_version: new_commit_key.number(),
+            });
+
             let signing_material = SigningMaterial {
                 secret_key,
                 pub_key,
@@ -241,6 +408,9 @@ mod tispark_client {
             Ok(())
         }
 
+        /// Produces a commitment for the service.
+        /// It is a query, so nothing is recorded on chain: once the commitment is stored by the chain of the service,
+        /// its proofs can be submitted to `record_commit` for that.
         #[ink(message)]
         fn commit(&self, request: CommitmentRequest) -> ContractResult<ContractCommitment> {
             self.ensure_service_contract(request.get_service())?;
@@ -279,25 +449,20 @@ mod tispark_client {
             Ok(commitment)
         }
 
+        /// Reveals a commitment of the service proven under a finalized block of its chain.
+        /// It is a query, so nothing is recorded on chain: the proofs can be submitted to `record_reveal` for that.
         #[ink(message)]
         fn reveal(&self, request: RevealCommitmentRequest) -> ContractResult<RevealResponse> {
-            self.ensure_service_contract(&request.service_id)?;
+            let service = self.ensure_service_contract(&request.service_id)?;
 
             let commit_id = H256::from(request);
             let rpc_request = self
-                .rpc
+                .rpcs
+                .get(service.chain)
+                .ok_or(ContractError::UnknownChain)?
                 .reveal_request(commit_id)
                 .map_err(|_| ContractError::RpcCallError)?;
-
-            // Verify the consensus proof
-            consensus::verify_consensus(&self.consensus_client, rpc_request.proof())
-                .map_err(|_| ContractError::InvalidConsensusProof)?;
-
-            // Verify a (key, value) pair within a state proof and a state commitment (state root hash)
-            // The state commitment has been validated through the consensus state proof that includes the state root hash
-            let res = state::verify_state(&self.consensus_client, rpc_request.response())
-                .map_err(|_| ContractError::ConsensusClientInvalidStateProof)?;
-            let res = CommitmentStateDecoder::decode(res)?;
+            let (block, res) = self.verify_commitment(&service, &commit_id, &rpc_request)?;
 
             let commitment_key = self.commitment_key();
 
@@ -315,7 +480,55 @@ mod tispark_client {
             .decrypt()
             .expect("The decryption in the reveal phase is expected to succeed");
 
-            Ok(RevealResponse::new(reveal_value, reveal_proof))
+            Ok(RevealResponse::new(reveal_value, reveal_proof, block))
+        }
+
+        /// Verifies the proofs of a commitment stored by the chain of the service and records it with a `Committed` event.
+        /// Anyone can submit them, since they are verified against the consensus client.
+        #[ink(message)]
+        fn record_commit(
+            &mut self,
+            service_id: ServiceId,
+            commit_id: H256,
+            proofs: RevealResultRequest,
+        ) -> ContractResult<VerifiedBlock> {
+            let service = self.ensure_service_exists(&service_id)?;
+            let (block, _) = self.verify_commitment(&service, &commit_id, &proofs)?;
+
+            self.env().emit_event(Committed {
+                service: service_id,
+                commit_id,
+                chain: service.chain,
+                block,
+            });
+            Ok(block)
+        }
+
+        /// Verifies the proofs of a revealed commitment and records it with a `Revealed` event.
+        /// Anyone can submit them, since they are verified against the consensus client.
+        #[ink(message)]
+        fn record_reveal(
+            &mut self,
+            request: RevealCommitmentRequest,
+            proofs: RevealResultRequest,
+        ) -> ContractResult<VerifiedBlock> {
+            let service_id = request.service_id;
+            let service = self.ensure_service_exists(&service_id)?;
+
+            let commit_id = H256::from(request);
+            let (block, commitment) = self.verify_commitment(&service, &commit_id, &proofs)?;
+            // The chain must have stored the revealed key
+            if !commitment.is_revealed() {
+                return Err(ContractError::CommitmentNotRevealed);
+            }
+
+            self.env().emit_event(Revealed {
+                service: service_id,
+                commit_id,
+                chain: service.chain,
+                block,
+            });
+            Ok(block)
         }
     }
 
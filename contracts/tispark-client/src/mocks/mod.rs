@@ -3,9 +3,12 @@ pub mod env_mocks {
     use hex::FromHex;
     use hkdf::Hkdf;
     use ink::primitives::AccountId;
-    use light_client::finality::crypto::{
-        verify as mock_verify, AuthorityId as MockAuthorityPublic,
-        AuthoritySignature as MockSignature,
+    use light_client::{
+        backend::NativeBackend,
+        finality::crypto::{
+            verify as mock_verify, AuthorityId as MockAuthorityPublic,
+            AuthoritySignature as MockSignature,
+        },
     };
     use pink_extension::chain_extension::mock as pink_mock;
     use rand::Rng;
@@ -31,7 +34,7 @@ pub mod env_mocks {
                 let auth_public = MockAuthorityPublic::try_from(pubkey)
                     .expect("Expected to be a valid public key");
                 let signature = MockSignature::try_from(sign).expect("Expected a valid signature");
-                mock_verify(&auth_public, msg, &signature.into())
+                mock_verify(&NativeBackend, &auth_public, msg, &signature.into())
             });
 
             // mocle the sign pink extension function
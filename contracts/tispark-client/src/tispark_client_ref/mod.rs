@@ -9,6 +9,7 @@ use ink::{
     env::call::{ExecutionInput, Selector},
     primitives::AccountId,
 };
+use light_client::VerifiedBlock;
 use scale::{Decode, Encode};
 use tispark_primitives::commit_reveal::{Commit, RevealProof};
 use utils::{types::Hash, ContractRef};
@@ -108,6 +109,8 @@ pub struct CommitmentPlainResponse<Metadata> {
 pub struct RevealPlainResponse<Value> {
     pub result: Value,
     pub proof: RevealProof,
+    /// Verified height, timestamp and state root of the block the reveal has been proven at
+    pub block: VerifiedBlock,
 }
 
 impl TisparkContractRef {
@@ -159,6 +162,7 @@ impl TisparkContractRef {
             Ok(RevealPlainResponse {
                 result,
                 proof: reveal_response.proof(),
+                block: reveal_response.block(),
             })
         })
     }
@@ -1,7 +1,10 @@
 use crate::{
     message::{RevealCommitmentRequest, RevealResponse},
     types::{commitment::ContractCommitment, message::CommitmentRequest, Result as ContractResult},
+    ServiceId,
 };
+use light_client::{Hash, VerifiedBlock};
+use tispark_rpc::RevealResultRequest;
 
 #[ink::trait_definition]
 pub trait CommitRevealContractManager {
@@ -14,4 +17,21 @@ pub trait CommitRevealContractManager {
 
     #[ink(message)]
     fn reveal(&self, request: RevealCommitmentRequest) -> ContractResult<RevealResponse>;
+
+    /// Verifies the proofs of a commitment stored by the chain of the service and records it on chain
+    #[ink(message)]
+    fn record_commit(
+        &mut self,
+        service_id: ServiceId,
+        commit_id: Hash,
+        proofs: RevealResultRequest,
+    ) -> ContractResult<VerifiedBlock>;
+
+    /// Verifies the proofs of a revealed commitment and records it on chain
+    #[ink(message)]
+    fn record_reveal(
+        &mut self,
+        request: RevealCommitmentRequest,
+        proofs: RevealResultRequest,
+    ) -> ContractResult<VerifiedBlock>;
 }
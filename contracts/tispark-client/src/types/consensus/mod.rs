@@ -1,5 +1,6 @@
-use aleph_consensus_client::{ConsensusContractResult, ConsensusProof};
+use aleph_consensus_client::{ConsensusContractResult, ConsensusProof, StateTrieResponseProof};
 use ink::env::call::{ExecutionInput, Selector};
+use light_client::VerifiedBlock;
 use utils::ContractRef;
 
 pub fn verify_consensus(
@@ -13,3 +14,17 @@ pub fn verify_consensus(
 
     contract.query(exec)
 }
+
+pub fn verify_block(
+    contract: &ContractRef,
+    consensus_proof: ConsensusProof,
+    timestamp: StateTrieResponseProof,
+) -> ConsensusContractResult<VerifiedBlock> {
+    let exec = ExecutionInput::new(Selector::new(ink::selector_bytes!(
+        "FinalityGadget::verify_block"
+    )))
+    .push_arg(consensus_proof)
+    .push_arg(timestamp);
+
+    contract.query(exec)
+}
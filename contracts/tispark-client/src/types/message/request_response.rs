@@ -1,6 +1,6 @@
 use crate::ServiceId;
 use alloc::vec::Vec;
-use light_client::Hash as H256;
+use light_client::{Hash as H256, VerifiedBlock};
 use scale::{Decode, Encode};
 use tispark_primitives::commit_reveal::RevealProof;
 use utils::types::Hash;
@@ -61,11 +61,21 @@ pub struct RevealResponse {
     result: Vec<u8>,
     /// bet commitment proof
     proof: RevealProof,
+    /// The finalized block at which the commitment has been proven
+    block: VerifiedBlock,
 }
 
 impl RevealResponse {
-    pub fn new(result: Vec<u8>, proof: RevealProof) -> Self {
-        Self { result, proof }
+    pub fn new(result: Vec<u8>, proof: RevealProof, block: VerifiedBlock) -> Self {
+        Self {
+            result,
+            proof,
+            block,
+        }
+    }
+
+    pub fn block(&self) -> VerifiedBlock {
+        self.block
     }
 
     pub fn result(&self) -> Vec<u8> {
@@ -2,6 +2,7 @@
 use self::message::{ContractPubKey, ContractSecretKey, ContractSigType};
 pub use self::Result as ContractResult;
 use crate::tispark_client::{KeyVersionInfo, KeyringVersion};
+use aleph_consensus_client::{ChainId, ContractError as ConsensusClientError};
 use core::fmt::Debug;
 use ink::primitives::AccountId;
 use scale::{Decode, Encode};
@@ -17,6 +18,17 @@ pub type Result<T> = core::result::Result<T, ContractError>;
 pub type ContractServiceId = AccountId;
 pub type ServiceId = u32;
 
+/// A registered service, its commitments are revealed through state proofs of the chain it is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ServiceRecord {
+    pub contract: ContractServiceId,
+    pub chain: ChainId,
+}
+
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ContractError {
@@ -45,6 +57,15 @@ pub enum ContractError {
     InvalidHex,
     InvalidHash,
     RpcCallError,
+    /// No rpc is registered for the chain
+    UnknownChain,
+    ChainAlreadyRegistered,
+    /// The proofs are not about the chain of the service
+    ChainMismatch,
+    /// The proofs are rejected by the consensus client
+    ConsensusClient(ConsensusClientError),
+    /// The commitment stored by the chain has not been revealed yet
+    CommitmentNotRevealed,
 }
 
 pub type VersionNumber = u32;
@@ -59,6 +80,12 @@ impl KeyringVersion {
         KeyringVersion(info)
     }
 
+    pub fn number(&self) -> VersionNumber {
+        match self.0 {
+            KeyVersionInfo::Signature(v) | KeyVersionInfo::Commitment(v) => v,
+        }
+    }
+
     pub fn increment(self) -> (Self, ContractSecretKey, ContractPubKey) {
         let new_version = match self.0 {
             KeyVersionInfo::Signature(v) => KeyVersionInfo::Signature(v + 1),
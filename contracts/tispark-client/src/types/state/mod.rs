@@ -1,12 +1,12 @@
 use super::ContractError;
 use crate::types::Result as ContractResult;
-use aleph_consensus_client::{ConsensusContractResult, StateTrieResponseProof};
+use aleph_consensus_client::{ConsensusContractResult, ConsensusProof, StateTrieResponseProof};
 use alloc::vec::Vec;
 use frame_support::traits::ConstU32;
 use ink::env::call::{ExecutionInput, Selector};
+use light_client::{state::TiSparkCommitment, VerifiedBlock};
 use scale::{Decode, Encode};
 use tispark_primitives::commit_reveal::SecretKey;
-use tispark_primitives::{ALGO_SIZE, IV_SIZE, MAX_COMMITMENT_SIZE, METADATA_SIZE};
 use utils::ContractRef;
 
 pub type Len<const T: u32> = ConstU32<T>;
@@ -26,6 +26,25 @@ pub fn verify_state(
     contract.query(exec)
 }
 
+/// Verifies the consensus proof, the timestamp and the state proof of the given key under the finalized state root, all at once
+pub fn verify_consensus_and_state(
+    contract: &ContractRef,
+    consensus_proof: ConsensusProof,
+    timestamp: StateTrieResponseProof,
+    state: StateTrieResponseProof,
+    key: Vec<u8>,
+) -> ConsensusContractResult<(VerifiedBlock, Vec<u8>)> {
+    let exec = ExecutionInput::new(Selector::new(ink::selector_bytes!(
+        "FinalityGadget::verify_consensus_and_state"
+    )))
+    .push_arg(consensus_proof)
+    .push_arg(timestamp)
+    .push_arg(state)
+    .push_arg(key);
+
+    contract.query(exec)
+}
+
 #[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
 pub struct ResultCommitment {
     commitment: Commitment,
@@ -48,22 +67,24 @@ impl ResultCommitment {
     pub fn key(&self) -> &[u8] {
         &self.proof
     }
+
+    /// Whether the chain has stored the revealed key of the commitment
+    pub fn is_revealed(&self) -> bool {
+        !self.proof.is_empty()
+    }
 }
 
 pub struct CommitmentStateDecoder;
 
 impl CommitmentStateDecoder {
+    /// Decodes the commitment as stored by the pallet, along with the revealed key (if any)
     pub fn decode(encoded: Vec<u8>) -> ContractResult<ResultCommitment> {
-        let commitment: pallet_commit_reveal::types::TiSparkCommitment<
-            Len<MAX_COMMITMENT_SIZE>,
-            Len<IV_SIZE>,
-            Len<ALGO_SIZE>,
-            Len<METADATA_SIZE>,
-        > = Decode::decode(&mut &encoded[..]).map_err(|_| ContractError::DecodeCommitStateError)?;
+        let commitment: TiSparkCommitment =
+            Decode::decode(&mut &encoded[..]).map_err(|_| ContractError::DecodeCommitStateError)?;
 
         Ok(ResultCommitment::new(
-            (commitment.get_data(), commitment.get_iv()),
-            Vec::new(),
+            (commitment.value().to_vec(), commitment.nonce().to_vec()),
+            commitment.key().to_vec(),
         ))
     }
 }
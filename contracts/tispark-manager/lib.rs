@@ -32,6 +32,15 @@ mod tispark_manager {
         admin: SudoAccount,
     }
 
+    /// The tispark client has been replaced
+    #[ink(event)]
+    pub struct ClientUpdated {
+        #[ink(topic)]
+        previous: ContractId,
+        #[ink(topic)]
+        client: ContractId,
+    }
+
     impl TisparkManager {
         /// Constructor to initializes your contract
         #[ink(constructor)]
@@ -56,7 +65,13 @@ mod tispark_manager {
         pub fn update_client(&mut self, id: ContractId) -> Result<()> {
             self.ensure_owner()?;
             // update tispark client
+            let previous = self.client.get();
             self.client = ContractRef::new(id);
+
+            self.env().emit_event(ClientUpdated {
+                previous,
+                client: id,
+            });
             Ok(())
         }
 
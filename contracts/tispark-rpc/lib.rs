@@ -4,7 +4,7 @@ extern crate alloc;
 mod types;
 
 // pink_extension is short for Phala ink! extension
-pub use self::tispark_rpc::TiSparkRpcRef;
+pub use self::{tispark_rpc::TiSparkRpcRef, types::RevealResultRequest};
 use pink_extension as pink;
 
 #[pink::contract(env=PinkEnvironment)]
@@ -12,11 +12,13 @@ mod tispark_rpc {
     use super::pink;
     use crate::types::{
         chain_state::{self, ChainStateHandler},
-        consensus::{ConsensusHandler, ConsensusProofParams},
+        consensus::{self, ConsensusHandler, ConsensusProofParams},
         CommitIdRequest, Error, ResponseStateProofRequest, Result, RevealResultRequest,
         StateRequestMetadata,
     };
-    use alloc::string::String;
+    use aleph_consensus_client::ConsensusEngine;
+    use alloc::{string::String, vec::Vec};
+    use light_client::state;
     use pink::PinkEnvironment;
     use utils::types::{AccessControl, SudoAccount};
 
@@ -26,18 +28,21 @@ mod tispark_rpc {
     pub struct TiSparkRpc {
         admin: SudoAccount,
         rpc_node: String,
+        /// Consensus engine of the chain served by the node
+        engine: ConsensusEngine,
     }
 
     impl TiSparkRpc {
         /// Constructor to initializes your contract
         #[ink(constructor)]
-        pub fn new(http_endpoint: String) -> Self {
+        pub fn new(http_endpoint: String, engine: ConsensusEngine) -> Self {
             let admin = pink::env().caller();
             let admin = SudoAccount::new(Some(admin));
 
             Self {
                 admin,
                 rpc_node: http_endpoint,
+                engine,
             }
         }
 
@@ -54,16 +59,14 @@ mod tispark_rpc {
 
             let endpoint = &self.rpc_node;
             // Some state request metadata
-            let meta = StateRequestMetadata {
-                id: id.clone(),
-                timestamp: self.env().block_timestamp(),
-                height: self.env().block_number() as u64,
-            };
+            let meta = StateRequestMetadata { id: id.clone() };
             // Storage key associated to the commitment id that is the key of the substrate StorageMap
             let storage_key = chain_state::build_storage_key_for_commitment(id.as_ref());
 
             // Handler for consensus related rpc calls
             let consensus_handler = ConsensusHandler::new(endpoint);
+            // The chain is identified by its genesis hash
+            let chain = consensus_handler.get_genesis_hash()?;
             // Last finalized block
             let finalized_block = consensus_handler.get_finalized_head()?;
             // Consensus state and justifications
@@ -74,12 +77,23 @@ mod tispark_rpc {
             let state_handler = ChainStateHandler::new(endpoint);
             // Storage read proof
             let storage_proof = state_handler.get_read_proof(&storage_key, &finalized_block)?;
-            // Untrasted authorities that eventually finalized the block
-            let untrusted_authorities =
-                state_handler.get_untrusted_authorities(&finalized_block)?;
+            // Timestamp of the finalized block, along with its read proof
+            let timestamp_key = state::timestamp_entry().key().to_vec();
+            let timestamp_proof = state_handler.get_read_proof(&timestamp_key, &finalized_block)?;
+            let timestamp = state_handler.get_timestamp(&finalized_block)?;
+            // Aleph authorities of the finalized block, only the signers are proven against their commitment.
+            // GRANDPA justifications carry the keys of their signers, BEEFY ones the Merkle proofs of their signers.
+            let signers = match self.engine {
+                ConsensusEngine::Aleph => {
+                    let authorities = state_handler.get_authorities(&finalized_block)?;
+                    consensus::signer_proofs(&authorities, &justifications)?
+                }
+                ConsensusEngine::Grandpa | ConsensusEngine::Beefy => Vec::new(),
+            };
 
             let consensus_proof = ConsensusProofParams {
-                untrusted_authorities,
+                chain,
+                signers,
                 justifications,
                 consensus_state,
             };
@@ -87,6 +101,8 @@ mod tispark_rpc {
             let request = ResponseStateProofRequest {
                 meta,
                 storage_proof,
+                timestamp_proof,
+                timestamp,
                 consensus_proof,
             };
 
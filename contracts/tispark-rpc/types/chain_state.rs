@@ -1,20 +1,17 @@
-use super::{Error, ReadProof, Result, UntrustedAuthorities, Utils};
+use super::{Error, ReadProof, Result, StorageData, Utils};
 use alloc::{format, string::String, vec, vec::Vec};
 use hex::FromHex;
+use light_client::{finality::crypto::AuthorityId, state};
 use scale::{Decode, Encode};
-use utils::types::{AuthorityId, Twox64Concat};
+use tispark_primitives::storage_keys::{StorageEntry, StorageHasher, StorageKeyBuilder};
 
-/// The encoded substrate storage key for the Authorities StorageValue
-const AUTH_STORAGE_KEY: &str = "0xd39f9508314957b74c787c4abb8c95bb5e0621c4869aa60c02be9adcc98a0d1d";
+/// Pallet and storage map holding the TISPARK commitments
+const COMMIT_REVEAL_PALLET: &str = "CommitReveal";
+const COMMITMENTS_MAP: &str = "PhatContractCommitment";
 
-/// TwoxHash of Pallet name CommitReveal
-const MODULE: [u8; 16] = [
-    0xa4, 0x5f, 0x72, 0x30, 0x93, 0x2f, 0xe9, 0xd5, 0xeb, 0xc8, 0x46, 0xb8, 0x73, 0xec, 0xd5, 0x3f,
-];
-/// TwoxHash of StorageMape Name PhatContractCommitment
-const METHOD: [u8; 16] = [
-    0xe2, 0xb9, 0x63, 0x43, 0x2a, 0xe5, 0x50, 0x77, 0x2d, 0xaa, 0x14, 0xb5, 0xf8, 0xe6, 0xe3, 0x97,
-];
+/// Pallet and storage value holding the Aleph authorities
+const ALEPH_PALLET: &str = "Aleph";
+const AUTHORITIES_VALUE: &str = "Authorities";
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -24,8 +21,14 @@ pub struct StorageProofParams {
 }
 
 pub fn build_storage_key_for_commitment(commit: &[u8]) -> Vec<u8> {
-    let twox_commit = Twox64Concat::hash(commit);
-    [&MODULE[..], &METHOD[..], &twox_commit[..]].concat()
+    StorageKeyBuilder::new(COMMIT_REVEAL_PALLET, COMMITMENTS_MAP)
+        .encoded_key(StorageHasher::Twox64Concat, commit)
+        .build()
+}
+
+/// Typed entry of the Aleph authorities
+fn authorities_entry() -> StorageEntry<Vec<AuthorityId>> {
+    StorageEntry::value(ALEPH_PALLET, AUTHORITIES_VALUE)
 }
 
 /// The handler of state rpc calls
@@ -69,24 +72,42 @@ impl<'a> ChainStateHandler<'a> {
         Ok(StorageProofParams { proof, keys })
     }
 
-    pub fn get_untrusted_authorities(&self, finalized_block: &String) -> Result<Vec<AuthorityId>> {
+    pub fn get_authorities(&self, finalized_block: &String) -> Result<Vec<AuthorityId>> {
+        let entry = authorities_entry();
+        let encoded_authorities = self.get_storage(entry.key(), finalized_block)?;
+
+        let authorities = entry
+            .decode(&encoded_authorities)
+            .map_err(|_| Error::AuthoritiesDecodeError)?;
+
+        Ok(authorities)
+    }
+
+    /// Timestamp of the finalized block (`pallet_timestamp::Now`), in milliseconds
+    pub fn get_timestamp(&self, finalized_block: &String) -> Result<u64> {
+        let entry = state::timestamp_entry();
+        let encoded_timestamp = self.get_storage(entry.key(), finalized_block)?;
+
+        let timestamp = entry
+            .decode(&encoded_timestamp)
+            .map_err(|_| Error::TimestampDecodeError)?;
+
+        Ok(timestamp)
+    }
+
+    fn get_storage(&self, storage_key: &[u8], block: &String) -> Result<Vec<u8>> {
+        let storage_key = format!("0x{}", Utils::encode_to_hex(storage_key));
         let data = format!(
             r#"{{"id":1,"jsonrpc":"2.0","method":"state_getStorage","params":["{}", "{}"]}}"#,
-            AUTH_STORAGE_KEY, finalized_block
+            storage_key, block
         )
         .into_bytes();
         let resp_body = Utils::call_rpc(&self.url, data)?;
 
-        let (authorities, _): (UntrustedAuthorities, usize) =
+        let (storage, _): (StorageData, usize) =
             serde_json_core::from_slice(&resp_body).or(Err(Error::InvalidBody))?;
 
-        let untrusted_authorities = Utils::extract_hex_from(2, authorities.result)?;
-        let encoded_authorities =
-            Vec::from_hex(untrusted_authorities).map_err(|_| Error::InvalidHexData)?;
-
-        let authorities: Vec<AuthorityId> = Decode::decode(&mut &encoded_authorities[..])
-            .map_err(|_| Error::AuthoritiesDecodeError)?;
-
-        Ok(authorities)
+        let encoded = Utils::extract_hex_from(2, storage.result)?;
+        Vec::from_hex(encoded).map_err(|_| Error::InvalidHexData)
     }
 }
@@ -1,4 +1,5 @@
 use super::{Error, FinalizedBlockHash, Result, SignedBlock, Utils};
+use aleph_consensus_client::ChainId;
 use alloc::{
     borrow::ToOwned,
     format,
@@ -6,9 +7,16 @@ use alloc::{
     vec::Vec,
 };
 use hex::FromHex;
-use light_client::{consensus::AlephLogs, Hash};
+use light_client::{
+    authority_set::{AuthorityProof, AuthoritySetCommitment},
+    consensus as light_consensus,
+    finality::{
+        crypto::AuthorityId,
+        justification::{backwards_compatible_decode, AlephJustification},
+    },
+    Hash,
+};
 use scale::{Decode, Encode};
-use utils::types::AuthorityId;
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -17,8 +25,8 @@ pub struct ConsensusStateParams {
     pub extrinsics_root: String,
     pub state_root: String,
     pub parent_hash: String,
-    pub aura_pre_runtime: String,
-    pub seal: String,
+    /// SCALE encoded header digest
+    pub digest: Vec<u8>,
 }
 
 impl<'a> TryFrom<SignedBlock<'a>> for ConsensusStateParams {
@@ -31,19 +39,22 @@ impl<'a> TryFrom<SignedBlock<'a>> for ConsensusStateParams {
         let extrinsics_root = Utils::extract_hex_from(2, block.result.block.header.extrinsicsRoot)?;
         let state_root = Utils::extract_hex_from(2, block.result.block.header.stateRoot)?;
         let parent_hash = Utils::extract_hex_from(2, block.result.block.header.parentHash)?;
-        // Digest Items
-        let aura_pre_runtime = block.result.block.header.digest.logs.get(0);
-        let aura_pre_runtime = Utils::extract_digest(14, aura_pre_runtime)?;
-        let seal = block.result.block.header.digest.logs.get(1);
-        let seal = Utils::extract_digest(16, seal)?;
+        // Digest Items, each one is a SCALE encoded `DigestItem`
+        let mut logs = Vec::new();
+        for log in block.result.block.header.digest.logs.iter() {
+            let log = Utils::extract_hex_from(2, log)?;
+            logs.push(Vec::from_hex(log).map_err(|_| Error::InvalidHexData)?);
+        }
+        let digest = light_consensus::digest_from_logs(&logs)
+            .map_err(|_| Error::InvalidDigest)?
+            .encode();
 
         Ok(Self {
             block: block_number.clone(),
             extrinsics_root,
             state_root,
             parent_hash,
-            aura_pre_runtime,
-            seal,
+            digest,
         })
     }
 }
@@ -83,6 +94,20 @@ impl<'a> ConsensusHandler<'a> {
 
         Ok(finalized_block.result.to_string())
     }
+
+    /// Hash of the genesis block, it identifies the chain
+    pub fn get_genesis_hash(&self) -> Result<ChainId> {
+        let data = r#"{"id":1, "jsonrpc":"2.0", "method": "chain_getBlockHash","params":[0]}"#
+            .to_string()
+            .into_bytes();
+        let resp_body = Utils::call_rpc(&self.url, data)?;
+        let (genesis, _): (FinalizedBlockHash, usize) =
+            serde_json_core::from_slice(&resp_body).or(Err(Error::InvalidBody))?;
+
+        let genesis = Utils::extract_hex_from(2, genesis.result)?;
+        let genesis = Vec::from_hex(genesis).map_err(|_| Error::InvalidHexData)?;
+        ChainId::try_from(genesis).map_err(|_| Error::InvalidHash)
+    }
 }
 
 fn get_justifications(justifications_response: &Vec<Vec<Vec<u8>>>) -> Result<Vec<u8>> {
@@ -94,10 +119,31 @@ fn get_justifications(justifications_response: &Vec<Vec<Vec<u8>>>) -> Result<Vec
     }
 }
 
+/// Proofs of the authorities that signed the justification, the contract only stores the commitment of the set
+pub fn signer_proofs(
+    authorities: &[AuthorityId],
+    justification: &[u8],
+) -> Result<Vec<AuthorityProof>> {
+    let justification = backwards_compatible_decode(justification.to_vec())
+        .map_err(|_| Error::InvalidJustificationsFormat)?;
+
+    match justification {
+        AlephJustification::CommitteeMultisignature(signatures) => {
+            AuthoritySetCommitment::signer_proofs(authorities, &signatures)
+                .ok_or(Error::InvalidAuthoritySet)
+        }
+        // Not signed by the committee
+        AlephJustification::EmergencySignature(_) => Ok(Vec::new()),
+    }
+}
+
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub struct ConsensusProofParams {
-    pub untrusted_authorities: Vec<AuthorityId>,
+    /// Genesis hash of the chain
+    pub chain: ChainId,
+    /// Authorities that signed the justification, along with their proofs in the authority set
+    pub signers: Vec<AuthorityProof>,
     pub justifications: Vec<u8>,
     pub consensus_state: ConsensusStateParams,
 }
@@ -128,10 +174,7 @@ impl ConsensusProofParams {
         Ok(Hash::from_slice(&parent_hash))
     }
 
-    pub fn logs(&self) -> AlephLogs {
-        AlephLogs {
-            aura_pre_runtime: self.consensus_state.aura_pre_runtime.clone(),
-            seal: self.consensus_state.seal.clone(),
-        }
+    pub fn digest(&self) -> Vec<u8> {
+        self.consensus_state.digest.clone()
     }
 }
@@ -26,11 +26,14 @@ pub enum Error {
     InvalidBody,
     InvalidHexData,
     HexStringOutOfBounds,
-    DigestItemMissing,
+    InvalidDigest,
     U32ConversionError,
     InvalidJustificationsFormat,
     AuthoritiesDecodeError,
+    InvalidAuthoritySet,
+    TimestampDecodeError,
     InvalidHash,
+    InvalidStateProof,
 }
 
 /// Type alias for the contract's result type.
@@ -102,7 +105,7 @@ pub struct ReadProofAtFinalizedBlock<'a> {
 }
 
 #[derive(Deserialize, Encode, Clone, Debug, PartialEq)]
-pub struct UntrustedAuthorities<'a> {
+pub struct StorageData<'a> {
     pub jsonrpc: &'a str,
     pub result: &'a str,
     pub id: u32,
@@ -134,21 +137,10 @@ impl Utils {
         if start_index >= hex_string.len() {
             return Err(Error::HexStringOutOfBounds);
         }
-        // 14
         let hex_string = &hex_string[start_index..];
         Ok(hex_string.to_string())
     }
 
-    fn extract_digest(start_index: usize, hex_string: Option<&&str>) -> Result<String> {
-        if let Some(digest) = hex_string {
-            // extract the digest item
-            let digest = Self::extract_hex_from(start_index, digest)?;
-            Ok(digest)
-        } else {
-            Err(Error::DigestItemMissing)
-        }
-    }
-
     fn encode_to_hex(value: &[u8]) -> String {
         hex::encode(value)
     }
@@ -158,25 +150,31 @@ impl Utils {
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub struct StateRequestMetadata {
     pub id: CommitIdRequest,
-    pub timestamp: u64,
-    pub height: u64,
 }
 
 /// Request to reveal the key binded to a commit
 /// The nonce_metadata is used as iv
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RevealResultRequest {
     response: StateTrieResponseProof,
     proof: ConsensusProof,
+    timestamp: StateTrieResponseProof,
     commit: CommitId,
 }
 
 impl RevealResultRequest {
-    pub fn new(response: StateTrieResponseProof, proof: ConsensusProof, commit: CommitId) -> Self {
+    pub fn new(
+        response: StateTrieResponseProof,
+        proof: ConsensusProof,
+        timestamp: StateTrieResponseProof,
+        commit: CommitId,
+    ) -> Self {
         Self {
             response,
             proof,
+            timestamp,
             commit,
         }
     }
@@ -189,6 +187,11 @@ impl RevealResultRequest {
         self.response.clone()
     }
 
+    /// State proof of the timestamp at the finalized block
+    pub fn timestamp(&self) -> StateTrieResponseProof {
+        self.timestamp.clone()
+    }
+
     pub fn commmit(&self) -> Hash {
         self.commit.clone()
     }
@@ -199,36 +202,47 @@ impl RevealResultRequest {
 pub struct ResponseStateProofRequest {
     pub meta: StateRequestMetadata,
     pub storage_proof: StorageProofParams,
+    pub timestamp_proof: StorageProofParams,
+    /// Untrusted timestamp of the finalized block, it is verified against `timestamp_proof`
+    pub timestamp: u64,
     pub consensus_proof: ConsensusProofParams,
 }
 
-impl TryFrom<ResponseStateProofRequest> for RevealResultRequest {
-    type Error = Error;
-    fn try_from(value: ResponseStateProofRequest) -> Result<Self> {
-        // 1. Build a commitment response proof
-
+impl ResponseStateProofRequest {
+    fn state_proof(&self, storage_proof: &StorageProofParams) -> Result<StateTrieResponseProof> {
         // Encoded Storage proof
         let proof = SubstrateStateProof {
             hasher: HashAlgorithm::Blake2,
-            storage_proof: value.storage_proof.proof.clone(),
+            storage_proof: storage_proof.proof.clone(),
         }
         .encode();
 
         let proof = Proof {
-            height: value.meta.height,
+            height: self.consensus_proof.block() as u64,
             proof,
         };
 
         let root = StateCommitment {
-            timestamp: value.meta.timestamp,
-            state_root: value.consensus_proof.state_root()?,
+            timestamp: self.timestamp,
+            state_root: self.consensus_proof.state_root()?,
         };
 
-        let commit_id = value.meta.id;
-        let keys = value.storage_proof.keys;
+        StateTrieResponseProof::new(
+            self.consensus_proof.chain,
+            GetResponseProof::new(&storage_proof.keys, &root, &proof),
+        )
+        .map_err(|_| Error::InvalidStateProof)
+    }
+}
 
-        let proof_request =
-            StateTrieResponseProof::new(GetResponseProof::new(&keys, &root, &proof)).unwrap();
+impl TryFrom<ResponseStateProofRequest> for RevealResultRequest {
+    type Error = Error;
+    fn try_from(value: ResponseStateProofRequest) -> Result<Self> {
+        // 1. Build a commitment response proof and a timestamp response proof
+        let proof_request = value.state_proof(&value.storage_proof)?;
+        let timestamp_request = value.state_proof(&value.timestamp_proof)?;
+
+        let commit_id = value.meta.id;
 
         // 2. Build a consensus proof
         let state = ConsensusState {
@@ -236,15 +250,23 @@ impl TryFrom<ResponseStateProofRequest> for RevealResultRequest {
             extrinsics_root: value.consensus_proof.extrinsics_root()?,
             state_root: value.consensus_proof.state_root()?,
             parent_hash: value.consensus_proof.parent_hash()?,
-            logs: value.consensus_proof.logs(),
+            digest: value.consensus_proof.digest(),
         };
 
         let consensus_proof = ConsensusProof {
+            chain: value.consensus_proof.chain,
             justification: value.consensus_proof.justifications,
             state,
-            untrusted_auth: value.consensus_proof.untrusted_authorities,
+            signers: value.consensus_proof.signers,
+            // Blocks served by the rpc are finalized by the committee
+            emergency_confirmation: None,
         };
 
-        Ok(Self::new(proof_request, consensus_proof, commit_id))
+        Ok(Self::new(
+            proof_request,
+            consensus_proof,
+            timestamp_request,
+            commit_id,
+        ))
     }
 }
@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use light_client::{
+    backend::{HeaderHasher, NativeBackend, SignatureVerifier},
+    consensus::AlephConsensusClient,
+    test_utils::MockCommittee,
+    ConsensusClient, Hash, Header,
+};
+
+/// Native backend that verifies signatures one at a time, as before batch verification
+struct SequentialBackend;
+
+impl SignatureVerifier for SequentialBackend {
+    fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        NativeBackend.verify_ed25519(public, message, signature)
+    }
+}
+
+impl HeaderHasher for SequentialBackend {
+    fn hash_header(&self, header: &Header) -> Hash {
+        NativeBackend.hash_header(header)
+    }
+}
+
+fn verify_consensus(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_consensus");
+    for size in [4, 16, 64, 128] {
+        let committee = MockCommittee::new(1, size);
+        let signatures = committee.sign(&Hash::repeat_byte(1));
+        let authorities = committee.authorities();
+
+        let sequential = AlephConsensusClient::with_backend(
+            SequentialBackend,
+            authorities.clone(),
+            authorities[0].clone(),
+        );
+        let batch = AlephConsensusClient::new(authorities.clone(), authorities[0].clone());
+
+        group.bench_with_input(BenchmarkId::new("sequential", size), &size, |b, _| {
+            b.iter(|| {
+                sequential
+                    .verify_consensus(black_box(Hash::repeat_byte(1)), signatures.clone())
+                    .unwrap()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), &size, |b, _| {
+            b.iter(|| {
+                batch
+                    .verify_consensus(black_box(Hash::repeat_byte(1)), signatures.clone())
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify_consensus);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+use crate::{backend::HeaderHasher, BlockNumber, Hash, Header};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AncestryError {
+    /// An ancestor does not hash to the parent hash of its child
+    BrokenChain,
+    /// An ancestor does not have the height right below its child
+    InvalidHeight,
+    /// The requested height is not covered by the ancestry proof
+    AncestorNotFound,
+}
+
+/// An ancestor header whose hash has been linked to a finalized header
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct VerifiedAncestor {
+    pub height: BlockNumber,
+    pub hash: Hash,
+    pub state_root: Hash,
+}
+
+/// A chain of ancestor headers of a finalized (justified) header, linked through their parent hashes.
+/// Headers go from the parent of the finalized header down to the oldest ancestor.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct AncestryProof {
+    ancestors: Vec<Header>,
+}
+
+impl AncestryProof {
+    pub fn new(ancestors: Vec<Header>) -> Self {
+        Self { ancestors }
+    }
+
+    pub fn ancestors(&self) -> &[Header] {
+        &self.ancestors
+    }
+
+    /// Verifies the whole chain down from the finalized header.
+    /// The finalized header must have already been verified against a justification.
+    pub fn verify<H: HeaderHasher + ?Sized>(
+        &self,
+        finalized: &Header,
+        hasher: &H,
+    ) -> Result<Vec<VerifiedAncestor>, AncestryError> {
+        let mut expected_hash = finalized.parent_hash;
+        let mut expected_height = finalized.number;
+
+        let mut verified = Vec::with_capacity(self.ancestors.len());
+        for ancestor in self.ancestors.iter() {
+            expected_height = expected_height
+                .checked_sub(1)
+                .ok_or(AncestryError::InvalidHeight)?;
+            if ancestor.number != expected_height {
+                return Err(AncestryError::InvalidHeight);
+            }
+
+            let hash = hasher.hash_header(ancestor);
+            if hash != expected_hash {
+                return Err(AncestryError::BrokenChain);
+            }
+
+            verified.push(VerifiedAncestor {
+                height: ancestor.number,
+                hash,
+                state_root: ancestor.state_root,
+            });
+            expected_hash = ancestor.parent_hash;
+        }
+
+        Ok(verified)
+    }
+
+    /// Verifies the chain and returns the ancestor at the given height, along with its state root.
+    pub fn ancestor_at<H: HeaderHasher + ?Sized>(
+        &self,
+        finalized: &Header,
+        height: BlockNumber,
+        hasher: &H,
+    ) -> Result<VerifiedAncestor, AncestryError> {
+        self.verify(finalized, hasher)?
+            .into_iter()
+            .find(|ancestor| ancestor.height == height)
+            .ok_or(AncestryError::AncestorNotFound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::NativeBackend;
+    use sp_runtime::{traits::Header as HeaderT, Digest};
+
+    /// Builds a chain of headers with a distinct state root each, from genesis up to the given height
+    fn chain(height: BlockNumber) -> Vec<Header> {
+        let mut headers: Vec<Header> = Vec::new();
+        for number in 0..=height {
+            let parent_hash = headers
+                .last()
+                .map(|parent| parent.hash())
+                .unwrap_or_default();
+            headers.push(Header::new(
+                number,
+                Hash::zero(),
+                Hash::repeat_byte(number as u8),
+                parent_hash,
+                Digest::default(),
+            ));
+        }
+        headers
+    }
+
+    #[test]
+    fn verifies_state_root_of_ancestor() {
+        let mut headers = chain(5);
+        let finalized = headers.pop().unwrap();
+        headers.reverse();
+
+        let proof = AncestryProof::new(headers);
+        let ancestor = proof.ancestor_at(&finalized, 2, &NativeBackend).unwrap();
+        assert_eq!(ancestor.height, 2);
+        assert_eq!(ancestor.state_root, Hash::repeat_byte(2));
+
+        assert_eq!(
+            proof.ancestor_at(&finalized, 5, &NativeBackend),
+            Err(AncestryError::AncestorNotFound)
+        );
+    }
+
+    #[test]
+    fn rejects_broken_chain() {
+        let mut headers = chain(5);
+        let finalized = headers.pop().unwrap();
+        headers.reverse();
+        // Tamper with the state root of an ancestor
+        headers[1].state_root = Hash::repeat_byte(42);
+
+        assert_eq!(
+            AncestryProof::new(headers).ancestor_at(&finalized, 1, &NativeBackend),
+            Err(AncestryError::BrokenChain)
+        );
+    }
+
+    #[test]
+    fn rejects_skipped_heights() {
+        let mut headers = chain(5);
+        let finalized = headers.pop().unwrap();
+        headers.reverse();
+        headers.remove(0);
+
+        assert_eq!(
+            AncestryProof::new(headers).verify(&finalized, &NativeBackend),
+            Err(AncestryError::InvalidHeight)
+        );
+    }
+}
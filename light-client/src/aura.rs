@@ -0,0 +1,124 @@
+/// Aura block authorship. Each slot is assigned to an authority in a round robin fashion,
+/// and the author seals the header by signing the hash of the header without the seal.
+use crate::{backend::Backend, consensus::AURA_ENGINE_ID, ConsensusError, Hash, Header};
+use alloc::vec::Vec;
+use codec::{Decode, DecodeAll, Encode};
+use scale_info::TypeInfo;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{traits::Header as HeaderT, Digest, DigestItem};
+
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"aura");
+
+mod app {
+    use sp_application_crypto::{app_crypto, sr25519};
+    app_crypto!(sr25519, super::KEY_TYPE);
+}
+
+sp_application_crypto::with_pair! {
+    pub type AuraPair = app::Pair;
+}
+pub type AuraId = app::Public;
+pub type AuraSignature = app::Signature;
+
+pub type Slot = u64;
+
+/// Author and slot of a block whose seal has been verified
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct VerifiedAuthor {
+    pub slot: Slot,
+    pub author: AuraId,
+}
+
+/// Decodes the slot from the Aura pre-runtime digest
+pub fn find_slot(digest: &Digest) -> Result<Slot, ConsensusError> {
+    digest
+        .logs
+        .iter()
+        .find_map(|log| match log {
+            DigestItem::PreRuntime(id, data) if *id == AURA_ENGINE_ID => Some(data),
+            _ => None,
+        })
+        .ok_or(ConsensusError::InvalidAuraSlot)
+        .and_then(|data| {
+            Slot::decode_all(&mut &data[..]).map_err(|_| ConsensusError::InvalidAuraSlot)
+        })
+}
+
+/// The authority expected to author the block at the given slot
+pub fn expected_author(slot: Slot, authorities: &[AuraId]) -> Option<&AuraId> {
+    if authorities.is_empty() {
+        return None;
+    }
+
+    let index = slot % authorities.len() as u64;
+    authorities.get(index as usize)
+}
+
+/// Hash of the header without its seal, that is what the author signs, along with the seal.
+/// The seal must be the last digest item.
+fn pre_seal_hash<B: Backend + ?Sized>(
+    header: &Header,
+    backend: &B,
+) -> Result<(Hash, Vec<u8>), ConsensusError> {
+    let mut header = header.clone();
+    let seal = match header.digest_mut().pop() {
+        Some(DigestItem::Seal(id, signature)) if id == AURA_ENGINE_ID => signature,
+        _ => return Err(ConsensusError::MissingAuraSeal),
+    };
+
+    Ok((backend.hash_header(&header), seal))
+}
+
+/// Verifies that the header has been sealed by the authority assigned to its slot
+pub fn verify_seal<B: Backend + ?Sized>(
+    header: &Header,
+    authorities: &[AuraId],
+    backend: &B,
+) -> Result<VerifiedAuthor, ConsensusError> {
+    let (pre_hash, seal) = pre_seal_hash(header, backend)?;
+    let slot = find_slot(header.digest())?;
+    let author = expected_author(slot, authorities).ok_or(ConsensusError::InvalidAuthorities)?;
+
+    if !backend.verify_sr25519(author.as_ref(), pre_hash.as_bytes(), &seal) {
+        return Err(ConsensusError::InvalidAuraSeal);
+    }
+
+    Ok(VerifiedAuthor {
+        slot,
+        author: author.clone(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{backend::NativeBackend, test_utils::sealed_header};
+    use sp_core::Pair;
+
+    #[test]
+    fn verifies_the_author_of_the_slot() {
+        let pairs: Vec<AuraPair> = (0..3u8)
+            .map(|index| AuraPair::from_seed(&[index; 32]))
+            .collect();
+        let authorities: Vec<AuraId> = pairs.iter().map(|pair| pair.public()).collect();
+
+        let header = sealed_header(&pairs[1], 1, 4);
+        let verified = verify_seal(&header, &authorities, &NativeBackend).unwrap();
+        assert_eq!(verified.slot, 4);
+        assert_eq!(verified.author, authorities[1]);
+
+        // Sealed by an authority that is not assigned to the slot
+        let header = sealed_header(&pairs[0], 1, 4);
+        assert!(matches!(
+            verify_seal(&header, &authorities, &NativeBackend),
+            Err(ConsensusError::InvalidAuraSeal)
+        ));
+
+        let mut unsealed = sealed_header(&pairs[1], 1, 4);
+        unsealed.digest_mut().pop();
+        assert!(matches!(
+            verify_seal(&unsealed, &authorities, &NativeBackend),
+            Err(ConsensusError::MissingAuraSeal)
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+/// Commitments to Aleph authority sets. Rather than the whole set, only the Merkle root of its keys is kept,
+/// and justifications come along with the keys of their signers and the proofs of their positions in the set.
+use crate::{
+    finality::{
+        crypto::{AlephSignature, AlephSignatureSet, AuthorityId},
+        types::{NodeCount, NodeIndex},
+    },
+    merkle::{merkle_proof, merkle_root, verify_merkle_proof},
+    ConsensusError, ContractBlakeTwo256, Hash,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// Keys of an authority set, by the position of the authorities in the set
+pub trait AuthorityKeys {
+    /// Size of the whole set
+    fn count(&self) -> NodeCount;
+
+    /// Key of the authority at the given position, if known
+    fn key(&self, index: NodeIndex) -> Option<&AuthorityId>;
+}
+
+impl AuthorityKeys for [AuthorityId] {
+    fn count(&self) -> NodeCount {
+        NodeCount(self.len())
+    }
+
+    fn key(&self, index: NodeIndex) -> Option<&AuthorityId> {
+        self.get(index.0)
+    }
+}
+
+impl AuthorityKeys for Vec<AuthorityId> {
+    fn count(&self) -> NodeCount {
+        self.as_slice().count()
+    }
+
+    fn key(&self, index: NodeIndex) -> Option<&AuthorityId> {
+        self.as_slice().key(index)
+    }
+}
+
+/// Merkle root of the keys of an authority set, in their order, along with the size of the set
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct AuthoritySetCommitment {
+    pub root: Hash,
+    pub len: u32,
+}
+
+impl AuthoritySetCommitment {
+    pub fn new(authorities: &[AuthorityId]) -> Self {
+        Self {
+            root: merkle_root::<ContractBlakeTwo256, _>(authorities),
+            len: authorities.len() as u32,
+        }
+    }
+
+    /// Builds the proof that the authority at the given position belongs to the set
+    pub fn member_proof(authorities: &[AuthorityId], index: usize) -> Option<AuthorityProof> {
+        let authority = authorities.get(index)?.clone();
+        let proof = merkle_proof::<ContractBlakeTwo256, _>(authorities, index)?;
+
+        Some(AuthorityProof {
+            index: index as u32,
+            authority,
+            proof,
+        })
+    }
+
+    /// Builds the proofs of the authorities that signed a justification
+    pub fn signer_proofs(
+        authorities: &[AuthorityId],
+        signatures: &AlephSignatureSet<AlephSignature>,
+    ) -> Option<Vec<AuthorityProof>> {
+        signatures
+            .iter()
+            .map(|(node_index, _)| Self::member_proof(authorities, NodeIndex::from(node_index).0))
+            .collect()
+    }
+
+    /// Verifies that each signer belongs to the committed set, at the position it claims
+    pub fn verify_signers(
+        &self,
+        signers: Vec<AuthorityProof>,
+    ) -> Result<CommittedSigners, ConsensusError> {
+        let count = self.len as usize;
+        let mut members = BTreeMap::new();
+        for signer in signers {
+            let leaf: &[u8] = signer.authority.as_ref();
+            if !verify_merkle_proof::<ContractBlakeTwo256>(
+                &self.root,
+                &signer.proof,
+                count,
+                signer.index as usize,
+                leaf,
+            ) {
+                return Err(ConsensusError::InvalidAuthorityProof);
+            }
+
+            let node_index = NodeIndex(signer.index as usize);
+            if members.insert(node_index, signer.authority).is_some() {
+                return Err(ConsensusError::DuplicateNodeIndex);
+            }
+        }
+
+        Ok(CommittedSigners {
+            count: NodeCount(count),
+            members,
+        })
+    }
+}
+
+/// An authority key along with the proof of its position in a committed authority set
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct AuthorityProof {
+    pub index: u32,
+    pub authority: AuthorityId,
+    pub proof: Vec<Hash>,
+}
+
+/// Authorities proven to belong to a committed set, the keys of the other authorities are unknown
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CommittedSigners {
+    count: NodeCount,
+    members: BTreeMap<NodeIndex, AuthorityId>,
+}
+
+impl AuthorityKeys for CommittedSigners {
+    fn count(&self) -> NodeCount {
+        self.count
+    }
+
+    fn key(&self, index: NodeIndex) -> Option<&AuthorityId> {
+        self.members.get(&index)
+    }
+}
+
+/// Authorities signatures are verified against
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Authorities {
+    /// The whole authority set
+    Full(Vec<AuthorityId>),
+    /// Only the signers of a justification, proven against a commitment
+    Committed(CommittedSigners),
+}
+
+impl From<Vec<AuthorityId>> for Authorities {
+    fn from(authorities: Vec<AuthorityId>) -> Self {
+        Authorities::Full(authorities)
+    }
+}
+
+impl From<CommittedSigners> for Authorities {
+    fn from(signers: CommittedSigners) -> Self {
+        Authorities::Committed(signers)
+    }
+}
+
+impl AuthorityKeys for Authorities {
+    fn count(&self) -> NodeCount {
+        match self {
+            Authorities::Full(authorities) => authorities.count(),
+            Authorities::Committed(signers) => signers.count(),
+        }
+    }
+
+    fn key(&self, index: NodeIndex) -> Option<&AuthorityId> {
+        match self {
+            Authorities::Full(authorities) => authorities.key(index),
+            Authorities::Committed(signers) => signers.key(index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        backend::{HeaderHasher, NativeBackend},
+        consensus::{AlephConsensusClient, Finality},
+        finality::justification::AlephJustification,
+        test_utils::{header, MockCommittee},
+    };
+
+    #[test]
+    fn committed_signers_finalize_blocks() {
+        let committee = MockCommittee::new(1, 5);
+        let authorities = committee.authorities();
+        let commitment = AuthoritySetCommitment::new(&authorities);
+        let finalizer = MockCommittee::new(2, 1).authorities()[0].clone();
+
+        let block = header(1, Hash::zero(), Hash::zero(), Vec::new());
+        let hash = NativeBackend.hash_header(&block);
+        let signatures = committee.sign_by(&hash, &[0, 1, 3, 4]);
+        let proofs = AuthoritySetCommitment::signer_proofs(&authorities, &signatures).unwrap();
+        assert_eq!(proofs.len(), 4);
+
+        let signers = commitment.verify_signers(proofs.clone()).unwrap();
+        let client = AlephConsensusClient::new(signers, finalizer.clone());
+        assert!(matches!(
+            client.verify_justification(
+                block.number,
+                hash,
+                AlephJustification::CommitteeMultisignature(signatures.clone()),
+                None
+            ),
+            Ok(Finality::Committee(_))
+        ));
+
+        // The key of a signer must be provided
+        let signers = commitment.verify_signers(proofs[1..].to_vec()).unwrap();
+        let client = AlephConsensusClient::new(signers, finalizer);
+        assert!(matches!(
+            client.verify_justification(
+                block.number,
+                hash,
+                AlephJustification::CommitteeMultisignature(signatures),
+                None
+            ),
+            Err(ConsensusError::MissingAuthorityKey)
+        ));
+
+        // Keys must be at the claimed position of the committed set
+        let mut misplaced = proofs[0].clone();
+        misplaced.index = 2;
+        assert!(matches!(
+            commitment.verify_signers(vec![misplaced]),
+            Err(ConsensusError::InvalidAuthorityProof)
+        ));
+        let outsider = MockCommittee::new(3, 1).authorities()[0].clone();
+        let forged = AuthorityProof {
+            authority: outsider,
+            ..proofs[0].clone()
+        };
+        assert!(commitment.verify_signers(vec![forged]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+/// Backends for signature verification and header hashing.
+/// The same light client runs natively (off-chain relayers, pallets and tests), in ink! contracts and in Phat Contracts,
+/// it only differs in how cryptographic primitives are provided by the environment.
+use crate::{ContractBlakeTwo256, Hash, Header};
+use codec::Encode;
+use pink_extension::{chain_extension::SigType, ext as contract_ext};
+use sp_core::{sr25519, Hasher};
+use sp_runtime::traits::{Header as HeaderT, Verify};
+
+/// A message signed by a public key, both the key and the signature are raw bytes
+#[derive(Clone, Copy, Debug)]
+pub struct SignedMessage<'a> {
+    pub public: &'a [u8],
+    pub message: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+/// Verifies an ed25519 signature by the ZIP-215 rules, as `sp_core` does.
+/// Batch verification follows the same rules, so both paths accept the same signatures.
+pub fn verify_ed25519_zip215(public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    match (
+        ed25519_zebra::VerificationKey::try_from(public),
+        ed25519_zebra::Signature::try_from(signature),
+    ) {
+        (Ok(public), Ok(signature)) => public.verify(&signature, message).is_ok(),
+        _ => false,
+    }
+}
+
+pub trait SignatureVerifier {
+    /// Verifies an ed25519 signature by the ZIP-215 rules. Malformed keys or signatures are not valid.
+    fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool;
+
+    /// Verifies a batch of ed25519 signatures, it holds only if all of them are valid.
+    /// By default signatures are verified one at a time.
+    fn verify_ed25519_batch(&self, batch: &[SignedMessage]) -> bool {
+        batch
+            .iter()
+            .all(|signed| self.verify_ed25519(signed.public, signed.message, signed.signature))
+    }
+
+    /// Verifies an sr25519 signature, such as Aura seals.
+    /// Backends without sr25519 support reject every signature.
+    fn verify_sr25519(&self, _public: &[u8], _message: &[u8], _signature: &[u8]) -> bool {
+        false
+    }
+}
+
+pub trait HeaderHasher {
+    fn hash_header(&self, header: &Header) -> Hash;
+}
+
+/// Everything a consensus client needs from the environment
+pub trait Backend: SignatureVerifier + HeaderHasher {}
+
+impl<T: SignatureVerifier + HeaderHasher> Backend for T {}
+
+/// Native environment, it relies on the host functions of `sp_io`
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct NativeBackend;
+
+impl SignatureVerifier for NativeBackend {
+    fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        verify_ed25519_zip215(public, message, signature)
+    }
+
+    fn verify_sr25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        match (
+            sr25519::Public::try_from(public),
+            sr25519::Signature::try_from(signature),
+        ) {
+            (Ok(public), Ok(signature)) => signature.verify(message, &public),
+            _ => false,
+        }
+    }
+
+    /// Off-chain the batch is verified at once, within a runtime signatures are verified one at a time
+    #[cfg(feature = "std")]
+    fn verify_ed25519_batch(&self, batch: &[SignedMessage]) -> bool {
+        crate::finality::crypto::randomized_batch_verify(batch)
+    }
+}
+
+impl HeaderHasher for NativeBackend {
+    fn hash_header(&self, header: &Header) -> Hash {
+        header.hash()
+    }
+}
+
+/// ink! contracts environment. There is no ed25519 host function, so signatures are verified within the contract.
+/// sr25519 signatures are not supported.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct InkBackend;
+
+impl SignatureVerifier for InkBackend {
+    fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        verify_ed25519_zip215(public, message, signature)
+    }
+}
+
+impl HeaderHasher for InkBackend {
+    fn hash_header(&self, header: &Header) -> Hash {
+        header.using_encoded(ContractBlakeTwo256::hash)
+    }
+}
+
+/// Phat Contracts environment, signatures are verified through the pink chain extension.
+/// The runtime verifies ed25519 signatures through `sp_core`, by the ZIP-215 rules as well.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PinkBackend;
+
+impl SignatureVerifier for PinkBackend {
+    fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        contract_ext().verify(SigType::Ed25519, public, message, signature)
+    }
+
+    fn verify_sr25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        contract_ext().verify(SigType::Sr25519, public, message, signature)
+    }
+}
+
+impl HeaderHasher for PinkBackend {
+    fn hash_header(&self, header: &Header) -> Hash {
+        header.using_encoded(ContractBlakeTwo256::hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sp_core::{ed25519, Pair};
+    use sp_runtime::Digest;
+
+    #[test]
+    fn backends_agree() {
+        let pair = ed25519::Pair::from_seed(&[7; 32]);
+        let message = b"finalized";
+        let public = pair.public();
+        let signature = pair.sign(message);
+
+        let signed = SignedMessage {
+            public: public.as_ref(),
+            message,
+            signature: signature.as_ref(),
+        };
+        assert!(NativeBackend.verify_ed25519_batch(&[signed]));
+        assert!(InkBackend.verify_ed25519_batch(&[signed]));
+        assert!(!NativeBackend.verify_ed25519(signed.public, b"other", signed.signature));
+        assert!(!InkBackend.verify_ed25519(signed.public, b"other", signed.signature));
+        assert!(!InkBackend.verify_ed25519(&[0; 31], message, signed.signature));
+
+        let header = Header::new(
+            1,
+            Hash::zero(),
+            Hash::zero(),
+            Hash::zero(),
+            Digest::default(),
+        );
+        assert_eq!(
+            NativeBackend.hash_header(&header),
+            InkBackend.hash_header(&header)
+        );
+        assert_eq!(
+            NativeBackend.hash_header(&header),
+            PinkBackend.hash_header(&header)
+        );
+    }
+
+    #[test]
+    fn single_and_batch_verification_share_the_validity_rule() {
+        // Identity key and commitment with a zero scalar, only valid by the cofactored ZIP-215 equation
+        let mut public = [0u8; 32];
+        public[0] = 1;
+        let mut signature = [0u8; 64];
+        signature[0] = 1;
+        let signed = SignedMessage {
+            public: &public,
+            message: b"finalized",
+            signature: &signature,
+        };
+
+        let single = NativeBackend.verify_ed25519(signed.public, signed.message, signed.signature);
+        assert!(single);
+        assert_eq!(NativeBackend.verify_ed25519_batch(&[signed]), single);
+        assert_eq!(
+            InkBackend.verify_ed25519(signed.public, signed.message, signed.signature),
+            single
+        );
+    }
+}
@@ -0,0 +1,218 @@
+/// Merkle Mountain Range leaf proofs, compatible with the ones generated by `pallet_mmr`.
+/// Positions follow the post-order numbering of nodes (starting from zero), as in `ckb_merkle_mountain_range`.
+use super::BeefyAuthoritySet;
+use crate::{BlockNumber, ContractKeccak256, Hash};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_core::Hasher;
+
+/// A leaf of the MMR built by `pallet_beefy_mmr`
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct MmrLeaf {
+    pub version: u8,
+    /// Number and hash of the parent of the block that appended the leaf
+    pub parent_number_and_hash: (BlockNumber, Hash),
+    pub beefy_next_authority_set: BeefyAuthoritySet,
+    /// Root of the parachain heads, or any other extra data of the chain
+    pub leaf_extra: Hash,
+}
+
+impl MmrLeaf {
+    pub fn hash(&self) -> Hash {
+        self.using_encoded(ContractKeccak256::hash)
+    }
+}
+
+/// Proof of a single leaf of the MMR
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct MmrLeafProof {
+    pub leaf_index: u64,
+    pub leaf_count: u64,
+    /// Left peaks, siblings from the leaf up to its peak and the bagged right peaks, in this order
+    pub items: Vec<Hash>,
+}
+
+impl MmrLeafProof {
+    /// Computes the MMR root from the leaf and the proof items
+    pub fn root(&self, leaf_hash: Hash) -> Option<Hash> {
+        if self.leaf_index >= self.leaf_count {
+            return None;
+        }
+
+        let mmr_size = leaf_index_to_mmr_size(self.leaf_count - 1);
+        let leaf_pos = leaf_index_to_pos(self.leaf_index);
+        let mut items = self.items.iter();
+
+        let all_peaks = get_peaks(mmr_size);
+        let mut peaks = Vec::new();
+        let mut has_right_peaks = false;
+        for (index, peak_pos) in all_peaks.iter().enumerate() {
+            if *peak_pos < leaf_pos {
+                peaks.push(*items.next()?);
+            } else {
+                peaks.push(climb_to_peak(leaf_hash, leaf_pos, *peak_pos, &mut items)?);
+                has_right_peaks = index + 1 < all_peaks.len();
+                break;
+            }
+        }
+        // Peaks on the right are bagged in a single item
+        if has_right_peaks {
+            peaks.push(*items.next()?);
+        }
+        if items.next().is_some() {
+            return None;
+        }
+
+        bag_peaks(peaks)
+    }
+
+    /// Verifies that the leaf is part of the MMR with the given root
+    pub fn verify(&self, root: &Hash, leaf: &MmrLeaf) -> bool {
+        self.root(leaf.hash())
+            .is_some_and(|computed| computed == *root)
+    }
+}
+
+fn climb_to_peak<'a>(
+    mut hash: Hash,
+    mut pos: u64,
+    peak_pos: u64,
+    items: &mut impl Iterator<Item = &'a Hash>,
+) -> Option<Hash> {
+    let mut height = 0;
+    while pos < peak_pos {
+        let sibling = items.next()?;
+        if pos_height_in_tree(pos + 1) > height {
+            // Right child
+            pos += 1;
+            hash = merge(sibling, &hash);
+        } else {
+            pos += parent_offset(height);
+            hash = merge(&hash, sibling);
+        }
+        height += 1;
+    }
+
+    (pos == peak_pos).then_some(hash)
+}
+
+fn bag_peaks(mut peaks: Vec<Hash>) -> Option<Hash> {
+    while peaks.len() > 1 {
+        let right = peaks.pop()?;
+        let left = peaks.pop()?;
+        peaks.push(merge(&right, &left));
+    }
+
+    peaks.pop()
+}
+
+fn merge(left: &Hash, right: &Hash) -> Hash {
+    let mut combined = [0u8; 64];
+    combined[..32].copy_from_slice(left.as_bytes());
+    combined[32..].copy_from_slice(right.as_bytes());
+    ContractKeccak256::hash(&combined)
+}
+
+fn leaf_index_to_mmr_size(index: u64) -> u64 {
+    let leaves_count = index + 1;
+    2 * leaves_count - leaves_count.count_ones() as u64
+}
+
+fn leaf_index_to_pos(index: u64) -> u64 {
+    leaf_index_to_mmr_size(index) - (index + 1).trailing_zeros() as u64 - 1
+}
+
+fn parent_offset(height: u32) -> u64 {
+    2 << height
+}
+
+fn sibling_offset(height: u32) -> u64 {
+    (2 << height) - 1
+}
+
+fn pos_height_in_tree(pos: u64) -> u32 {
+    let mut pos = pos + 1;
+    let all_ones = |num: u64| num != 0 && num.count_zeros() == num.leading_zeros();
+    while !all_ones(pos) {
+        let most_significant_bit = 1 << (63 - pos.leading_zeros());
+        pos -= most_significant_bit - 1;
+    }
+
+    63 - pos.leading_zeros()
+}
+
+fn get_peaks(mmr_size: u64) -> Vec<u64> {
+    let peak_pos_by_height = |height: u32| (1u64 << (height + 1)) - 2;
+
+    // The left peak is the highest one
+    let mut height = 0;
+    while peak_pos_by_height(height + 1) < mmr_size {
+        height += 1;
+    }
+    let mut pos = peak_pos_by_height(height);
+
+    let mut peaks = Vec::new();
+    peaks.push(pos);
+    loop {
+        // Move to the right sibling, then down to the left child until the position is in the MMR
+        pos += sibling_offset(height);
+        while pos > mmr_size - 1 {
+            if height == 0 {
+                return peaks;
+            }
+            pos -= parent_offset(height - 1);
+            height -= 1;
+        }
+        peaks.push(pos);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn leaf(i: u8) -> Hash {
+        Hash::repeat_byte(i)
+    }
+
+    fn proof(leaf_index: u64, leaf_count: u64, items: Vec<Hash>) -> MmrLeafProof {
+        MmrLeafProof {
+            leaf_index,
+            leaf_count,
+            items,
+        }
+    }
+
+    #[test]
+    fn computes_peaks() {
+        assert_eq!(get_peaks(1), vec![0]);
+        assert_eq!(get_peaks(3), vec![2]);
+        assert_eq!(get_peaks(4), vec![2, 3]);
+        assert_eq!(get_peaks(11), vec![6, 9, 10]);
+        assert_eq!(leaf_index_to_pos(4), 7);
+        assert_eq!(pos_height_in_tree(6), 2);
+    }
+
+    #[test]
+    fn computes_root_from_leaf_proofs() {
+        // Three leaves: a peak of height one with the first two leaves, and the third leaf as a peak
+        let left_peak = merge(&leaf(0), &leaf(1));
+        let root = merge(&leaf(2), &left_peak);
+
+        assert_eq!(
+            proof(0, 3, vec![leaf(1), leaf(2)]).root(leaf(0)),
+            Some(root)
+        );
+        assert_eq!(
+            proof(1, 3, vec![leaf(0), leaf(2)]).root(leaf(1)),
+            Some(root)
+        );
+        assert_eq!(proof(2, 3, vec![left_peak]).root(leaf(2)), Some(root));
+
+        // Extra or missing items are rejected
+        assert_eq!(proof(2, 3, vec![left_peak, leaf(0)]).root(leaf(2)), None);
+        assert_eq!(proof(0, 3, vec![leaf(1)]).root(leaf(0)), None);
+        assert_eq!(proof(3, 3, vec![]).root(leaf(3)), None);
+    }
+}
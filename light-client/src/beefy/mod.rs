@@ -0,0 +1,472 @@
+use crate::{
+    backend::{HeaderHasher, NativeBackend},
+    consensus::FinalityReport,
+    finality::types::{NodeCount, NodeIndex},
+    hash_encoded,
+    merkle::verify_merkle_proof,
+    BlockNumber, ConsensusClient, ConsensusError, ContractKeccak256, Hash, Header, Proof,
+};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use mmr::{MmrLeaf, MmrLeafProof};
+use scale_info::TypeInfo;
+use sp_core::Hasher;
+
+pub mod mmr;
+
+/// Identifier of a BEEFY validator set
+pub type ValidatorSetId = u64;
+/// Identifier of a payload item
+pub type PayloadId = [u8; 2];
+/// Recoverable ECDSA signature (`r ++ s ++ v`)
+pub type EcdsaSignature = [u8; 65];
+/// Ethereum address of a validator, the leaves of the validator set Merkle tree
+pub type EthAddress = [u8; 20];
+
+/// Payload item of the MMR root
+pub const MMR_ROOT_ID: PayloadId = *b"mh";
+
+/// A BEEFY validator set, as it is committed in the MMR leaves (lifted from `sp_consensus_beefy::mmr`)
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct BeefyAuthoritySet {
+    pub id: ValidatorSetId,
+    pub len: u32,
+    /// Merkle root of the Ethereum addresses of the validators
+    pub keyset_commitment: Hash,
+}
+
+impl BeefyAuthoritySet {
+    /// BEEFY threshold, more than 2/3 of the validators (`len - (len - 1) / 3`).
+    /// An empty validator set can not sign anything.
+    pub fn threshold(&self) -> Result<NodeCount, ConsensusError> {
+        let len = self.len as usize;
+        if len == 0 {
+            return Err(ConsensusError::InvalidAuthorities);
+        }
+
+        Ok(NodeCount(len - (len - 1) / 3))
+    }
+}
+
+/// Payload of a commitment, items are sorted by their identifier (lifted from `sp_consensus_beefy`)
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct Payload(pub Vec<(PayloadId, Vec<u8>)>);
+
+impl Payload {
+    /// Decodes the first item with the given identifier
+    pub fn get_decoded<T: Decode>(&self, id: &PayloadId) -> Option<T> {
+        self.0
+            .iter()
+            .find(|(item_id, _)| item_id == id)
+            .and_then(|(_, value)| T::decode(&mut &value[..]).ok())
+    }
+}
+
+/// What BEEFY validators sign (lifted from `sp_consensus_beefy`)
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct Commitment {
+    pub payload: Payload,
+    pub block_number: BlockNumber,
+    pub validator_set_id: ValidatorSetId,
+}
+
+impl Commitment {
+    /// Validators sign the Keccak hash of the encoded commitment
+    pub fn hash(&self) -> Hash {
+        self.using_encoded(ContractKeccak256::hash)
+    }
+}
+
+/// A validator signature along with the Merkle proof of the validator address in the validator set
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct ValidatorSignature {
+    pub index: u32,
+    pub signature: EcdsaSignature,
+    pub proof: Vec<Hash>,
+}
+
+/// A commitment signed by a subset of the validators, large enough to reach the threshold
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct SignedCommitment {
+    pub commitment: Commitment,
+    pub signatures: Vec<ValidatorSignature>,
+}
+
+/// Proof of a header through a signed commitment and the MMR leaf that holds the header hash
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct BeefyProof {
+    pub signed_commitment: SignedCommitment,
+    /// Leaf appended by the child of the header
+    pub leaf: MmrLeaf,
+    pub leaf_proof: MmrLeafProof,
+    pub header: Header,
+}
+
+impl core::hash::Hash for BeefyProof {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_encoded(self, state)
+    }
+}
+
+impl Proof for BeefyProof {}
+
+/// A header whose state root has been verified through a BEEFY commitment
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct VerifiedBeefyHeader {
+    pub height: BlockNumber,
+    pub hash: Hash,
+    pub state_root: Hash,
+    /// Block of the commitment that proved the header
+    pub commitment_block: BlockNumber,
+}
+
+/// Recovers the Ethereum address of the signer, it relies on the ink! environment both natively and within contracts
+fn recover_address(signature: &EcdsaSignature, message_hash: &Hash) -> Option<EthAddress> {
+    let mut public = [0u8; 33];
+    ink_env::ecdsa_recover(signature, message_hash.as_fixed_bytes(), &mut public).ok()?;
+    let mut address = [0u8; 20];
+    ink_env::ecdsa_to_eth_address(&public, &mut address).ok()?;
+
+    Some(address)
+}
+
+/// BEEFY consensus client implementation.
+/// It only keeps the Merkle roots of the current and next validator sets, signers prove their membership.
+/// Signatures are recovered through `ink_env`, the backend only hashes headers.
+pub struct BeefyConsensusClient<H = NativeBackend> {
+    current: BeefyAuthoritySet,
+    next: BeefyAuthoritySet,
+    hasher: H,
+}
+
+impl BeefyConsensusClient {
+    pub fn new(current: BeefyAuthoritySet, next: BeefyAuthoritySet) -> Self {
+        Self::with_backend(NativeBackend, current, next)
+    }
+}
+
+impl<H: HeaderHasher> BeefyConsensusClient<H> {
+    pub fn with_backend(hasher: H, current: BeefyAuthoritySet, next: BeefyAuthoritySet) -> Self {
+        Self {
+            current,
+            next,
+            hasher,
+        }
+    }
+
+    pub fn current(&self) -> &BeefyAuthoritySet {
+        &self.current
+    }
+
+    pub fn next(&self) -> &BeefyAuthoritySet {
+        &self.next
+    }
+
+    /// Verifies that a commitment is signed by enough validators of the current or of the next set
+    pub fn verify_commitment(
+        &self,
+        signed_commitment: &SignedCommitment,
+    ) -> Result<FinalityReport, ConsensusError> {
+        let commitment = &signed_commitment.commitment;
+        let validator_set = [self.current, self.next]
+            .into_iter()
+            .find(|set| set.id == commitment.validator_set_id)
+            .ok_or(ConsensusError::UnknownValidatorSet)?;
+        let threshold = validator_set.threshold()?;
+
+        let len = validator_set.len as usize;
+        let message_hash = commitment.hash();
+        let mut signers = Vec::new();
+        for signed in signed_commitment.signatures.iter() {
+            let node_index = NodeIndex(signed.index as usize);
+            if node_index.0 >= len {
+                return Err(ConsensusError::MissingAuthorityKey);
+            }
+            if signers.contains(&node_index) {
+                return Err(ConsensusError::DuplicateNodeIndex);
+            }
+
+            let address = recover_address(&signed.signature, &message_hash)
+                .ok_or(ConsensusError::InvalidSignature)?;
+            if !verify_merkle_proof::<ContractKeccak256>(
+                &validator_set.keyset_commitment,
+                &signed.proof,
+                len,
+                node_index.0,
+                &address,
+            ) {
+                return Err(ConsensusError::InvalidValidatorProof);
+            }
+
+            signers.push(node_index);
+        }
+
+        let achieved = NodeCount(signers.len());
+        if achieved < threshold {
+            return Err(ConsensusError::NotEnoughSignatures);
+        }
+
+        let missing = (0..len)
+            .map(NodeIndex)
+            .filter(|node_index| !signers.contains(node_index))
+            .collect();
+
+        Ok(FinalityReport {
+            signers,
+            missing,
+            threshold,
+            achieved,
+        })
+    }
+
+    /// Verifies the commitment and the MMR leaf proof, then the header through the hash committed in the leaf.
+    pub fn verify_header(
+        &self,
+        proof: &BeefyProof,
+    ) -> Result<(VerifiedBeefyHeader, FinalityReport), ConsensusError> {
+        let report = self.verify_commitment(&proof.signed_commitment)?;
+
+        let commitment = &proof.signed_commitment.commitment;
+        let mmr_root: Hash = commitment
+            .payload
+            .get_decoded(&MMR_ROOT_ID)
+            .ok_or(ConsensusError::MissingMmrRoot)?;
+        if !proof.leaf_proof.verify(&mmr_root, &proof.leaf) {
+            return Err(ConsensusError::InvalidMmrProof);
+        }
+
+        let hash = self.hasher.hash_header(&proof.header);
+        if proof.leaf.parent_number_and_hash != (proof.header.number, hash) {
+            return Err(ConsensusError::InvalidMmrProof);
+        }
+
+        let header = VerifiedBeefyHeader {
+            height: proof.header.number,
+            hash,
+            state_root: proof.header.state_root,
+            commitment_block: commitment.block_number,
+        };
+
+        Ok((header, report))
+    }
+
+    /// Verifies a header and follows the validator set changes.
+    /// A commitment signed by the next set makes it the current one, and leaves announce the following set.
+    pub fn import(&mut self, proof: &BeefyProof) -> Result<VerifiedBeefyHeader, ConsensusError> {
+        let (header, _) = self.verify_header(proof)?;
+        let announced = proof.leaf.beefy_next_authority_set;
+        if announced.id > self.next.id {
+            // The announced set must be able to sign commitments
+            announced.threshold()?;
+        }
+
+        if proof.signed_commitment.commitment.validator_set_id == self.next.id
+            && self.next.id != self.current.id
+        {
+            self.current = self.next;
+        }
+        if announced.id > self.next.id {
+            self.next = announced;
+        }
+
+        Ok(header)
+    }
+}
+
+impl<H: HeaderHasher> ConsensusClient for BeefyConsensusClient<H> {
+    type ConsensusProof = BeefyProof;
+
+    /// The consensus state is the hash of the header proven through the MMR
+    fn verify_consensus(
+        &self,
+        consensus_state: Hash,
+        proof: Self::ConsensusProof,
+    ) -> Result<FinalityReport, ConsensusError> {
+        let (header, report) = self.verify_header(&proof)?;
+        if header.hash != consensus_state {
+            return Err(ConsensusError::InvalidMmrProof);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::merkle::{merkle_proof, merkle_root};
+    use sp_core::{ecdsa, Pair};
+    use sp_runtime::traits::Header as HeaderT;
+
+    fn address(pair: &ecdsa::Pair) -> EthAddress {
+        let public: &[u8] = pair.public().as_ref();
+        let public: [u8; 33] = public.try_into().unwrap();
+        let mut address = [0u8; 20];
+        ink_env::ecdsa_to_eth_address(&public, &mut address).unwrap();
+        address
+    }
+
+    fn sign(pairs: &[ecdsa::Pair], signers: &[usize], commitment: Commitment) -> SignedCommitment {
+        let addresses: Vec<EthAddress> = pairs.iter().map(address).collect();
+        let signatures = signers
+            .iter()
+            .map(|index| {
+                let signature = pairs[*index].sign_prehashed(commitment.hash().as_fixed_bytes());
+                let signature: &[u8] = signature.as_ref();
+                ValidatorSignature {
+                    index: *index as u32,
+                    signature: signature.try_into().unwrap(),
+                    proof: merkle_proof::<ContractKeccak256, _>(&addresses, *index).unwrap(),
+                }
+            })
+            .collect();
+
+        SignedCommitment {
+            commitment,
+            signatures,
+        }
+    }
+
+    #[test]
+    fn verifies_header_through_mmr() {
+        let pairs: Vec<ecdsa::Pair> = (0..4).map(|_| ecdsa::Pair::generate().0).collect();
+        let addresses: Vec<EthAddress> = pairs.iter().map(address).collect();
+        let set = BeefyAuthoritySet {
+            id: 0,
+            len: 4,
+            keyset_commitment: merkle_root::<ContractKeccak256, _>(&addresses),
+        };
+        let next_set = BeefyAuthoritySet { id: 1, ..set };
+        let client = BeefyConsensusClient::new(set, next_set);
+
+        let header = Header::new(
+            41,
+            Hash::zero(),
+            Hash::repeat_byte(7),
+            Hash::zero(),
+            Default::default(),
+        );
+        let leaf = MmrLeaf {
+            version: 0,
+            parent_number_and_hash: (41, header.hash()),
+            beefy_next_authority_set: next_set,
+            leaf_extra: Hash::zero(),
+        };
+        // A single leaf MMR, its root is the leaf hash
+        let commitment = Commitment {
+            payload: Payload(vec![(MMR_ROOT_ID, leaf.hash().encode())]),
+            block_number: 42,
+            validator_set_id: 0,
+        };
+        let leaf_proof = MmrLeafProof {
+            leaf_index: 0,
+            leaf_count: 1,
+            items: Vec::new(),
+        };
+
+        let proof = BeefyProof {
+            signed_commitment: sign(&pairs, &[0, 1, 3], commitment.clone()),
+            leaf,
+            leaf_proof,
+            header,
+        };
+        let (verified, report) = client.verify_header(&proof).unwrap();
+        assert_eq!(verified.state_root, Hash::repeat_byte(7));
+        assert_eq!(report.missing, vec![NodeIndex(2)]);
+
+        let not_enough = BeefyProof {
+            signed_commitment: sign(&pairs, &[0, 1], commitment.clone()),
+            ..proof.clone()
+        };
+        assert!(matches!(
+            client.verify_header(&not_enough),
+            Err(ConsensusError::NotEnoughSignatures)
+        ));
+
+        // A signature that does not match the claimed index
+        let mut wrong_index = sign(&pairs, &[0, 1, 3], commitment);
+        wrong_index.signatures[2].index = 2;
+        let wrong_index = BeefyProof {
+            signed_commitment: wrong_index,
+            ..proof
+        };
+        assert!(matches!(
+            client.verify_header(&wrong_index),
+            Err(ConsensusError::InvalidValidatorProof)
+        ));
+    }
+
+    /// Proof of a header signed by the given validators, its leaf announces the given set
+    fn signed_proof(
+        pairs: &[ecdsa::Pair],
+        validator_set_id: ValidatorSetId,
+        announced: BeefyAuthoritySet,
+    ) -> BeefyProof {
+        let header = Header::new(
+            41,
+            Hash::zero(),
+            Hash::repeat_byte(7),
+            Hash::zero(),
+            Default::default(),
+        );
+        let leaf = MmrLeaf {
+            version: 0,
+            parent_number_and_hash: (41, header.hash()),
+            beefy_next_authority_set: announced,
+            leaf_extra: Hash::zero(),
+        };
+        let commitment = Commitment {
+            payload: Payload(vec![(MMR_ROOT_ID, leaf.hash().encode())]),
+            block_number: 42,
+            validator_set_id,
+        };
+        let signers: Vec<usize> = (0..pairs.len()).collect();
+
+        BeefyProof {
+            signed_commitment: sign(pairs, &signers, commitment),
+            leaf,
+            leaf_proof: MmrLeafProof {
+                leaf_index: 0,
+                leaf_count: 1,
+                items: Vec::new(),
+            },
+            header,
+        }
+    }
+
+    #[test]
+    fn empty_validator_sets_are_rejected() {
+        let pairs: Vec<ecdsa::Pair> = (0..4).map(|_| ecdsa::Pair::generate().0).collect();
+        let addresses: Vec<EthAddress> = pairs.iter().map(address).collect();
+        let set = BeefyAuthoritySet {
+            id: 0,
+            len: 4,
+            keyset_commitment: merkle_root::<ContractKeccak256, _>(&addresses),
+        };
+        let empty = BeefyAuthoritySet {
+            id: 1,
+            len: 0,
+            keyset_commitment: Hash::zero(),
+        };
+
+        // A commitment of an empty set, with no signature at all
+        let client = BeefyConsensusClient::new(empty, empty);
+        let unsigned = signed_proof(&[], 1, empty);
+        assert!(matches!(
+            client.verify_commitment(&unsigned.signed_commitment),
+            Err(ConsensusError::InvalidAuthorities)
+        ));
+
+        // An empty set announced by a leaf is not followed
+        let mut client = BeefyConsensusClient::new(set, BeefyAuthoritySet { id: 0, ..set });
+        assert!(matches!(
+            client.import(&signed_proof(&pairs, 0, empty)),
+            Err(ConsensusError::InvalidAuthorities)
+        ));
+        assert_eq!(client.next().id, 0);
+
+        let next = BeefyAuthoritySet { id: 1, ..set };
+        assert!(client.import(&signed_proof(&pairs, 0, next)).is_ok());
+        assert_eq!(client.next(), &next);
+    }
+}
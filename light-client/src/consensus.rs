@@ -1,20 +1,25 @@
 use crate::{
+    ancestry::AncestryProof,
+    aura::{self, AuraId, VerifiedAuthor},
+    authority_set::{Authorities, AuthorityKeys},
+    backend::{Backend, NativeBackend, SignatureVerifier, SignedMessage},
     finality::{
         crypto::{
-            verify, verify_from_contract, AlephSignature, AlephSignatureSet, AuthorityId,
+            verify, verify_batch, AlephSignature, AlephSignatureSet, AuthorityId,
             AuthoritySignature,
         },
-        types::NodeIndex,
+        justification::AlephJustification,
+        types::{NodeCount, NodeIndex, NodeSubset},
     },
-    BlockHash, BlockNumber, ConsensusClient, ConsensusError, ContractBlakeTwo256, Hash, Header,
-    Proof,
+    misbehaviour::{Equivocation, Misbehaviour},
+    version::FinalityVersionSchedule,
+    BlockHash, BlockNumber, ConsensusClient, ConsensusError, Hash, Header, Proof,
 };
 
-use alloc::{string::String, vec::Vec};
-use codec::{Decode, Encode};
+use alloc::vec::Vec;
+use codec::{Decode, DecodeAll, Encode};
 use hex::FromHex;
 use scale_info::TypeInfo;
-use sp_core::Hasher;
 use sp_runtime::{traits::Header as HeaderT, ConsensusEngineId, Digest, DigestItem};
 
 /// The `ConsensusEngineId` of AuRa.
@@ -23,12 +28,6 @@ pub const AURA_ENGINE_ID: ConsensusEngineId = *b"aura";
 /// The `ConsensusEngineId` of Aleph.
 pub const ALEPH_ENGINE_ID: ConsensusEngineId = *b"FRNK";
 
-#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
-pub struct AlephLogs {
-    pub aura_pre_runtime: String,
-    pub seal: String,
-}
-
 #[allow(dead_code)]
 pub struct AlephConsensusLogBuilder {
     aura_pre_runtime: Vec<u8>,
@@ -60,108 +59,401 @@ impl AlephConsensusLogBuilder {
     }
 }
 
-/// AlephBFT consensus client implementation
-pub struct AlephConsensusClient {
-    authorities: Vec<AuthorityId>,
-    // Not yet supported for state verify
+/// Decodes a SCALE encoded header digest, that can hold any kind of digest item
+pub fn decode_digest(encoded: &[u8]) -> Result<Digest, ConsensusError> {
+    Digest::decode_all(&mut &encoded[..]).map_err(|_| ConsensusError::InvalidDigest)
+}
+
+/// Builds a header digest from its SCALE encoded digest items, in the same order as they appear in the header
+pub fn digest_from_logs(logs: &[Vec<u8>]) -> Result<Digest, ConsensusError> {
+    let logs = logs
+        .iter()
+        .map(|log| DigestItem::decode_all(&mut &log[..]).map_err(|_| ConsensusError::InvalidDigest))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Digest { logs })
+}
+
+/// Policy that defines how many authorities must sign a block for it to be final
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub enum FinalityThreshold {
+    /// More than 2/3 of the authorities, as required by Aleph
+    Supermajority,
+    /// More than `numerator / denominator` of the authorities
+    Fraction { numerator: u32, denominator: u32 },
+    /// A fixed amount of authorities
+    Fixed(u32),
+}
+
+impl Default for FinalityThreshold {
+    fn default() -> Self {
+        FinalityThreshold::Supermajority
+    }
+}
+
+impl FinalityThreshold {
+    /// Number of signatures required for a set of authorities.
+    /// The policy can only make the threshold stricter, more than 2/3 of the authorities are always required.
+    pub fn required(&self, authorities: NodeCount) -> NodeCount {
+        let required = match self {
+            FinalityThreshold::Supermajority => authorities.consensus_threshold(),
+            FinalityThreshold::Fraction {
+                numerator,
+                denominator,
+            } => {
+                if *denominator == 0 {
+                    // Not satisfiable
+                    authorities + 1
+                } else {
+                    // Computed on 64 bits, so that it does not overflow on wasm32
+                    let required = (authorities.0 as u64).saturating_mul(*numerator as u64)
+                        / (*denominator as u64)
+                        + 1;
+                    NodeCount(usize::try_from(required).unwrap_or(usize::MAX))
+                }
+            }
+            FinalityThreshold::Fixed(count) => NodeCount(*count as usize),
+        };
+
+        required.max(authorities.consensus_threshold())
+    }
+}
+
+/// Outcome of a successful finality verification
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FinalityReport {
+    /// Authorities with a valid signature
+    pub signers: Vec<NodeIndex>,
+    /// Authorities that did not sign
+    pub missing: Vec<NodeIndex>,
+    /// Number of signatures required by the threshold policy
+    pub threshold: NodeCount,
+    /// Number of valid signatures
+    pub achieved: NodeCount,
+}
+
+/// Policy on blocks finalized by the emergency finalizer rather than by the committee
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub enum EmergencyFinalityPolicy {
+    /// Emergency finality is never accepted
+    Reject,
+    /// Emergency finality is accepted only for blocks within the given (inclusive) ranges
+    BlockRanges(Vec<(BlockNumber, BlockNumber)>),
+    /// Emergency finality is accepted once a later block, descending from it, is finalized by the committee
+    RequireCommitteeConfirmation,
+}
+
+impl Default for EmergencyFinalityPolicy {
+    fn default() -> Self {
+        EmergencyFinalityPolicy::Reject
+    }
+}
+
+/// A later block finalized by the committee, linked by its ancestry to a block finalized by the emergency finalizer
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct EmergencyConfirmation {
+    pub header: Header,
+    pub justification: AlephSignatureSet<AlephSignature>,
+    pub ancestry: AncestryProof,
+}
+
+/// How a block has been finalized
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Finality {
+    /// Finalized by the committee
+    Committee(FinalityReport),
+    /// Finalized by the emergency finalizer, along with the height of the block that confirmed it (if required by the policy)
+    Emergency { confirmed_by: Option<BlockNumber> },
+}
+
+impl Finality {
+    pub fn is_emergency(&self) -> bool {
+        matches!(self, Finality::Emergency { .. })
+    }
+}
+
+/// Outcome of the verification of a finalized header
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct VerifiedHeader {
+    pub hash: Hash,
+    pub finality: Finality,
+    /// Only known if the Aura seal is verified
+    pub author: Option<VerifiedAuthor>,
+}
+
+/// Verifies a committee multisignature over the consensus state, with respect to the finality threshold
+pub(crate) fn verify_signature_set<K, V>(
+    authorities: &K,
+    threshold: &FinalityThreshold,
+    consensus_state: &Hash,
+    proof: AlephSignatureSet<AlephSignature>,
+    verifier: &V,
+) -> Result<FinalityReport, ConsensusError>
+where
+    K: AuthorityKeys + ?Sized,
+    V: SignatureVerifier + ?Sized,
+{
+    let authority_count = authorities.count();
+    // The signature set must be built for the current authority set
+    if proof.size() != authority_count {
+        return Err(ConsensusError::InvalidSignatureSetSize);
+    }
+
+    let mut signed = NodeSubset::with_size(authority_count);
+    let mut signers = Vec::new();
+    let mut signatures = Vec::new();
+    for (node_index, sign) in proof.into_iter() {
+        let node_index = NodeIndex::from(node_index);
+        let authority_key = authorities
+            .key(node_index)
+            .ok_or(ConsensusError::MissingAuthorityKey)?;
+
+        if signed.contains(node_index) {
+            return Err(ConsensusError::DuplicateNodeIndex);
+        }
+
+        signed.insert(node_index);
+        signers.push(node_index);
+        signatures.push((authority_key, sign));
+    }
+
+    // Signatures are verified all at once, so that the backend can batch them
+    let batch: Vec<SignedMessage> = signatures
+        .iter()
+        .map(|(authority_key, sign)| SignedMessage {
+            public: authority_key.as_ref(),
+            message: consensus_state.as_bytes(),
+            signature: sign.as_ref(),
+        })
+        .collect();
+    verify_batch(verifier, &batch).map_err(|invalid| {
+        // Every signature holds on its own, the backend does not verify batches by the same rules
+        if invalid.is_empty() {
+            return ConsensusError::InconsistentBatchVerification;
+        }
+        ConsensusError::InvalidSigners(
+            invalid
+                .into_iter()
+                .map(|position| signers[position])
+                .collect(),
+        )
+    })?;
+
+    let threshold = threshold.required(authority_count);
+    let achieved = NodeCount(signers.len());
+    if achieved < threshold {
+        return Err(ConsensusError::NotEnoughSignatures);
+    }
+
+    let missing = authority_count
+        .into_iterator()
+        .filter(|node_index| !signed.contains(*node_index))
+        .collect();
+
+    Ok(FinalityReport {
+        signers,
+        missing,
+        threshold,
+        achieved,
+    })
+}
+
+/// AlephBFT consensus client implementation.
+/// It is generic over the backend that verifies signatures and hashes headers, that is native by default.
+pub struct AlephConsensusClient<B = NativeBackend> {
+    /// Either the whole set or only the signers proven against its commitment
+    authorities: Authorities,
+    /// Its signatures are accepted according to the emergency finality policy
     emergency_finalizer: AuthorityId,
+    emergency_policy: EmergencyFinalityPolicy,
+    threshold: FinalityThreshold,
+    versions: FinalityVersionSchedule,
+    /// If set, the Aura seal of headers is verified against these authorities
+    aura_authorities: Option<Vec<AuraId>>,
+    backend: B,
 }
 
 impl Proof for AlephSignatureSet<AlephSignature> {}
 
-impl ConsensusClient for AlephConsensusClient {
+impl<B: Backend> ConsensusClient for AlephConsensusClient<B> {
     type ConsensusProof = AlephSignatureSet<AlephSignature>;
 
     fn verify_consensus(
         &self,
         consensus_state: Hash,
         proof: Self::ConsensusProof,
-    ) -> Result<Vec<NodeIndex>, ConsensusError> {
-        let mut authorities = Vec::new();
-
-        for (node_index, sign) in proof.into_iter() {
-            if let Some(authority_key) = self.authorities.get(node_index.0) {
-                if verify(authority_key, &consensus_state.0, &sign) {
-                    authorities.push(NodeIndex::from(node_index));
-                } else {
-                    return Err(ConsensusError::InvalidSignature);
-                }
-            } else {
-                return Err(ConsensusError::MissingAuthorityKey);
-            }
-        }
+    ) -> Result<FinalityReport, ConsensusError> {
+        verify_signature_set(
+            &self.authorities,
+            &self.threshold,
+            &consensus_state,
+            proof,
+            &self.backend,
+        )
+    }
+}
 
-        Ok(authorities)
+impl AlephConsensusClient {
+    pub fn new(authorities: impl Into<Authorities>, emergency_finalizer: AuthorityId) -> Self {
+        Self::with_backend(NativeBackend, authorities, emergency_finalizer)
     }
 }
 
 #[allow(dead_code)]
-impl AlephConsensusClient {
-    pub fn new(authorities: Vec<AuthorityId>, emergency_finalizer: AuthorityId) -> Self {
+impl<B: Backend> AlephConsensusClient<B> {
+    pub fn with_backend(
+        backend: B,
+        authorities: impl Into<Authorities>,
+        emergency_finalizer: AuthorityId,
+    ) -> Self {
         Self {
-            authorities,
+            authorities: authorities.into(),
             emergency_finalizer,
+            emergency_policy: Default::default(),
+            threshold: Default::default(),
+            versions: Default::default(),
+            aura_authorities: None,
+            backend,
         }
     }
 
-    /// Verifies the proof of a sudo account
-    pub fn verify_consensus_sudo(
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Sets the emergency finality policy, by default emergency finality is rejected
+    pub fn with_emergency_policy(mut self, emergency_policy: EmergencyFinalityPolicy) -> Self {
+        self.emergency_policy = emergency_policy;
+        self
+    }
+
+    /// Sets the finality threshold policy, by default a supermajority is required
+    pub fn with_threshold(mut self, threshold: FinalityThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the schedule of finality versions, by default legacy justification formats are accepted
+    pub fn with_versions(mut self, versions: FinalityVersionSchedule) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    /// Enables the verification of the Aura seal of headers, by default the seal is not verified
+    pub fn with_aura_authorities(mut self, authorities: Vec<AuraId>) -> Self {
+        self.aura_authorities = Some(authorities);
+        self
+    }
+
+    /// Verifies the Aura seal of the header, if enabled.
+    /// It returns the author and the slot of the header, or `None` if the seal is not verified.
+    pub fn verify_author(&self, header: &Header) -> Result<Option<VerifiedAuthor>, ConsensusError> {
+        self.aura_authorities
+            .as_ref()
+            .map(|authorities| aura::verify_seal(header, authorities, &self.backend))
+            .transpose()
+    }
+
+    /// Verifies the finality of a header and, if enabled, its Aura seal
+    pub fn verify_header(
         &self,
-        consensus_state: BlockHash,
-        proof: AuthoritySignature,
-    ) -> bool {
-        let sign = AlephSignature::from(proof);
-        verify(&self.emergency_finalizer, &consensus_state.0, &sign)
+        header: &Header,
+        justification: AlephJustification,
+        confirmation: Option<EmergencyConfirmation>,
+    ) -> Result<VerifiedHeader, ConsensusError> {
+        let hash = self.backend.hash_header(header);
+        let finality =
+            self.verify_justification(header.number, hash, justification, confirmation)?;
+        let author = self.verify_author(header)?;
+
+        Ok(VerifiedHeader {
+            hash,
+            finality,
+            author,
+        })
     }
 
-    pub fn build_consenus_state(
+    /// Decodes the justification of a block according to the finality version active at that block
+    pub fn decode_justification(
         &self,
         block: BlockNumber,
-        extrinsics_root: Hash,
-        state_root: Hash,
-        parent_hash: BlockHash,
-        digest: Digest,
-    ) -> BlockHash {
-        Header::new(block, extrinsics_root, state_root, parent_hash, digest).hash()
+        justification: Vec<u8>,
+    ) -> Result<AlephJustification, ConsensusError> {
+        self.versions
+            .decode_justification(block, justification)
+            .map_err(|_| ConsensusError::InvalidJustification)
     }
-}
 
-pub struct PhatContractConsensusClient(AlephConsensusClient);
-
-impl ConsensusClient for PhatContractConsensusClient {
-    type ConsensusProof = AlephSignatureSet<AlephSignature>;
-
-    fn verify_consensus(
+    /// Verifies the justification of a block, either from the committee or from the emergency finalizer.
+    /// A confirmation is needed for emergency justifications, if the policy requires it.
+    pub fn verify_justification(
         &self,
+        block: BlockNumber,
         consensus_state: Hash,
-        proof: Self::ConsensusProof,
-    ) -> Result<Vec<NodeIndex>, ConsensusError> {
-        let aleph_client = &self.0;
-
-        let mut authorities = Vec::new();
-        for (node_index, sign) in proof.into_iter() {
-            if let Some(authority_key) = aleph_client.authorities.get(node_index.0) {
-                if verify_from_contract(authority_key, &consensus_state.0, &sign) {
-                    authorities.push(NodeIndex::from(node_index));
+        justification: AlephJustification,
+        confirmation: Option<EmergencyConfirmation>,
+    ) -> Result<Finality, ConsensusError> {
+        let signature = match justification {
+            AlephJustification::CommitteeMultisignature(signatures) => {
+                return self
+                    .verify_consensus(consensus_state, signatures)
+                    .map(Finality::Committee);
+            }
+            AlephJustification::EmergencySignature(signature) => AlephSignature::from(signature),
+        };
+
+        if !verify(
+            &self.backend,
+            &self.emergency_finalizer,
+            &consensus_state.0,
+            &signature,
+        ) {
+            return Err(ConsensusError::InvalidSignature);
+        }
+
+        match &self.emergency_policy {
+            EmergencyFinalityPolicy::Reject => Err(ConsensusError::EmergencyFinalityRejected),
+            EmergencyFinalityPolicy::BlockRanges(ranges) => {
+                if ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&block))
+                {
+                    Ok(Finality::Emergency { confirmed_by: None })
                 } else {
-                    return Err(ConsensusError::InvalidSignature);
+                    Err(ConsensusError::EmergencyFinalityRejected)
                 }
-            } else {
-                return Err(ConsensusError::MissingAuthorityKey);
             }
-        }
+            EmergencyFinalityPolicy::RequireCommitteeConfirmation => {
+                let confirmation =
+                    confirmation.ok_or(ConsensusError::EmergencyFinalityUnconfirmed)?;
+
+                // The confirming block must be finalized by the committee
+                let confirmed_by = confirmation.header.number;
+                let confirming_state = self.backend.hash_header(&confirmation.header);
+                self.verify_consensus(confirming_state, confirmation.justification)?;
 
-        Ok(authorities)
+                // And it must descend from the emergency finalized block
+                let ancestor = confirmation
+                    .ancestry
+                    .ancestor_at(&confirmation.header, block, &self.backend)
+                    .map_err(|_| ConsensusError::EmergencyFinalityUnconfirmed)?;
+                if ancestor.hash != consensus_state {
+                    return Err(ConsensusError::EmergencyFinalityUnconfirmed);
+                }
+
+                Ok(Finality::Emergency {
+                    confirmed_by: Some(confirmed_by),
+                })
+            }
+        }
     }
-}
 
-#[allow(dead_code)]
-impl PhatContractConsensusClient {
-    pub fn new(authorities: Vec<AuthorityId>, emergency_finalizer: AuthorityId) -> Self {
-        Self(AlephConsensusClient {
-            authorities,
-            emergency_finalizer,
-        })
+    /// Verifies evidence of two conflicting headers, both finalized by the authorities
+    pub fn verify_misbehaviour(
+        &self,
+        misbehaviour: &Misbehaviour,
+    ) -> Result<Equivocation, ConsensusError> {
+        misbehaviour.verify(&self.authorities, &self.threshold, &self.backend)
     }
 
     /// Verifies the proof of a sudo account
@@ -170,10 +462,13 @@ impl PhatContractConsensusClient {
         consensus_state: BlockHash,
         proof: AuthoritySignature,
     ) -> bool {
-        let alep_client = &self.0;
-
         let sign = AlephSignature::from(proof);
-        verify_from_contract(&alep_client.emergency_finalizer, &consensus_state.0, &sign)
+        verify(
+            &self.backend,
+            &self.emergency_finalizer,
+            &consensus_state.0,
+            &sign,
+        )
     }
 
     pub fn build_consenus_state(
@@ -181,20 +476,10 @@ impl PhatContractConsensusClient {
         block: BlockNumber,
         extrinsics_root: Hash,
         state_root: Hash,
-        parent_hash: Hash,
+        parent_hash: BlockHash,
         digest: Digest,
-    ) -> Hash {
-        ink_hash_header(block, extrinsics_root, state_root, parent_hash, digest)
+    ) -> BlockHash {
+        let header = Header::new(block, extrinsics_root, state_root, parent_hash, digest);
+        self.backend.hash_header(&header)
     }
 }
-
-pub fn ink_hash_header(
-    block: BlockNumber,
-    extrinsics_root: Hash,
-    state_root: Hash,
-    parent_hash: Hash,
-    digest: Digest,
-) -> Hash {
-    Header::new(block, extrinsics_root, state_root, parent_hash, digest)
-        .using_encoded(|consensus_state| ContractBlakeTwo256::hash(consensus_state))
-}
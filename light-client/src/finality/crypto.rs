@@ -1,10 +1,9 @@
 use super::types::{AlephNodeIndex, NodeCount, PartialMultisignature, Signature, SignatureSet};
-use alloc::boxed::Box;
+use crate::backend::{SignatureVerifier, SignedMessage};
+use alloc::{boxed::Box, vec::Vec};
 use codec::{Decode, Encode};
-use pink_extension::{chain_extension::SigType, ext as contract_ext};
 use scale_info::TypeInfo;
 use sp_core::crypto::KeyTypeId;
-use sp_runtime::RuntimeAppPublic;
 
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"alp0");
 
@@ -19,20 +18,63 @@ sp_application_crypto::with_pair! {
 pub type AuthoritySignature = app::Signature;
 pub type AuthorityId = app::Public;
 
-/// Verify the signature given an authority id.
-pub fn verify(authority: &AuthorityId, message: &[u8], signature: &AlephSignature) -> bool {
-    authority.verify(&message, &signature.get())
-}
-
-/// Verify the signature given an authority id from a phat contract
-pub fn verify_from_contract(
+/// Verify the signature given an authority id, through the signature verification backend.
+pub fn verify<V: SignatureVerifier + ?Sized>(
+    verifier: &V,
     authority: &AuthorityId,
     message: &[u8],
     signature: &AlephSignature,
 ) -> bool {
-    let key = authority.to_raw_vec();
-    let signature = signature.get().to_vec();
-    contract_ext().verify(SigType::Ed25519, &key, message, &signature)
+    verifier.verify_ed25519(authority.as_ref(), message, signature.as_ref())
+}
+
+/// Verify a batch of signatures at once, through the signature verification backend.
+/// If the batch does not hold, signatures are verified one at a time to find the invalid ones,
+/// whose positions in the batch are returned. No position is returned if the backend rejects a batch of valid signatures.
+pub fn verify_batch<V: SignatureVerifier + ?Sized>(
+    verifier: &V,
+    batch: &[SignedMessage],
+) -> Result<(), Vec<usize>> {
+    if verifier.verify_ed25519_batch(batch) {
+        return Ok(());
+    }
+
+    let invalid = batch
+        .iter()
+        .enumerate()
+        .filter(|(_, signed)| {
+            !verifier.verify_ed25519(signed.public, signed.message, signed.signature)
+        })
+        .map(|(position, _)| position)
+        .collect();
+
+    Err(invalid)
+}
+
+/// Randomized batch verification of ed25519 signatures.
+/// Signatures are combined with random coefficients, so that it holds only if all of them are valid (with overwhelming probability).
+/// It follows the ZIP-215 rules, as the verification of single signatures does.
+#[cfg(feature = "std")]
+pub fn randomized_batch_verify(batch: &[SignedMessage]) -> bool {
+    use ed25519_zebra::{batch, Signature, VerificationKeyBytes};
+
+    let mut verifier = batch::Verifier::new();
+    for signed in batch {
+        let (Ok(public), Ok(signature)) = (
+            <[u8; 32]>::try_from(signed.public),
+            <[u8; 64]>::try_from(signed.signature),
+        ) else {
+            return false;
+        };
+
+        verifier.queue((
+            VerificationKeyBytes::from(public),
+            Signature::from(signature),
+            &signed.message,
+        ));
+    }
+
+    verifier.verify(rand_core::OsRng).is_ok()
 }
 
 /// Wrapper for `SignatureSet` to be able to implement both legacy and current `PartialMultisignature` trait.
@@ -85,6 +127,12 @@ impl AlephSignature {
     }
 }
 
+impl AsRef<[u8]> for AlephSignature {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
 impl From<AuthoritySignature> for AlephSignature {
     fn from(authority_signature: AuthoritySignature) -> AlephSignature {
         AlephSignature(authority_signature)
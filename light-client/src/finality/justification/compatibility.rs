@@ -125,6 +125,7 @@ impl Decode for VersionedAlephJustification {
 pub enum Error {
     BadFormat,
     UnknownVersion(Version),
+    UnexpectedVersion(Version),
 }
 
 fn decode_pre_compatibility_justification(
@@ -173,11 +174,79 @@ pub fn backwards_compatible_decode(
     }
 }
 
+/// Decodes a justification that must be encoded with the given version.
+/// Unlike `backwards_compatible_decode` it never falls back to legacy formats.
+pub fn versioned_decode(
+    justification_raw: Vec<u8>,
+    expected: Version,
+) -> Result<AlephJustification, Error> {
+    use Error::*;
+    use VersionedAlephJustification::*;
+    let justification = VersionedAlephJustification::decode_all(&mut justification_raw.as_slice())
+        .map_err(|_| BadFormat)?;
+
+    let version = match &justification {
+        Other(version, _) => *version,
+        V1(_) => Version(1),
+        V2(_) => Version(2),
+        V3(_) => Version(3),
+    };
+    if version != expected {
+        return Err(UnexpectedVersion(version));
+    }
+
+    match justification {
+        V1(justification) => Ok(justification.into()),
+        V2(justification) => Ok(justification.into()),
+        V3(justification) => Ok(justification),
+        Other(version, _) => Err(UnknownVersion(version)),
+    }
+}
+
 /// Encodes the justification in a way that is forwards compatible with future versions.
 pub fn versioned_encode(justification: AlephJustification) -> Vec<u8> {
     VersionedAlephJustification::V3(justification).encode()
 }
 
+/// Encodes the justification in the format of the given version, either prefixed with the version or as it was
+/// produced before the compatibility changes. Legacy formats can only hold committee signatures.
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn encode_as(
+    justification: AlephJustification,
+    version: Version,
+    versioned: bool,
+) -> Option<Vec<u8>> {
+    use VersionedAlephJustification::*;
+    let justification = match (justification, version) {
+        (justification, Version(3)) => V3(justification),
+        (AlephJustification::CommitteeMultisignature(signature), Version(2)) => {
+            V2(AlephJustificationV2 { signature })
+        }
+        (AlephJustification::CommitteeMultisignature(signatures), Version(1)) => {
+            let size = signatures.size();
+            let signature = signatures.into_iter().fold(
+                AlephSignatureSet::with_size(size),
+                |sig_set, (id, sgn)| {
+                    let sgn = SignatureV1 {
+                        _id: id,
+                        sgn: sgn.get(),
+                    };
+                    sig_set.add_signature(&sgn, id)
+                },
+            );
+            V1(AlephJustificationV1 { signature })
+        }
+        _ => return None,
+    };
+
+    match (versioned, justification) {
+        (true, justification) => Some(justification.encode()),
+        (false, V1(justification)) => Some(justification.encode()),
+        (false, V2(justification)) => Some(justification.encode()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use codec::{Decode, Encode};
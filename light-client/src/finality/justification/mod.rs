@@ -1,9 +1,14 @@
 /// Lifted directly from [`finality_aleph::justification`](https://github.com/Cardinal-Cryptography/aleph-node/blob/main/finality-aleph/src/justification/mod.rs)
 use crate::consensus::ALEPH_ENGINE_ID;
 use codec::{Decode, Encode};
-pub use compatibility::{backwards_compatible_decode, versioned_encode, Error as DecodeError};
+pub use compatibility::{
+    backwards_compatible_decode, versioned_decode, versioned_encode, Error as DecodeError,
+};
 use sp_runtime::Justification;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) use compatibility::encode_as;
+
 use super::crypto::{AlephSignature, AlephSignatureSet, AuthoritySignature};
 mod compatibility;
 
@@ -20,6 +25,23 @@ pub enum AlephJustification {
     EmergencySignature(AuthoritySignature),
 }
 
+/// Justifications are represented by their hex versioned encoding
+#[cfg(feature = "serde")]
+impl serde::Serialize for AlephJustification {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        primitives::serde_hex::bytes::serialize(&versioned_encode(self.clone()), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AlephJustification {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = primitives::serde_hex::bytes::deserialize(deserializer)?;
+        backwards_compatible_decode(encoded)
+            .map_err(|_| <D::Error as serde::de::Error>::custom("invalid justification"))
+    }
+}
+
 impl From<AlephJustification> for Justification {
     fn from(val: AlephJustification) -> Self {
         (ALEPH_ENGINE_ID, versioned_encode(val))
@@ -1,7 +1,7 @@
 use alloc::{boxed::Box, vec, vec::Vec};
 use codec::{Codec, Decode, Encode, Error, Input, Output};
 use derive_more::{From, Into};
-use scale_info::prelude::ops::{Div, Mul};
+use scale_info::prelude::ops::{Add, Div, Mul};
 
 /// The index of a node
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, From, Into)]
@@ -62,7 +62,7 @@ pub trait Index {
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, From, Into)]
 pub struct NodeCount(pub usize);
 
-// deriving Mul and Div is somehow cumbersome
+// deriving Mul, Add and Div is somehow cumbersome
 impl Mul<usize> for NodeCount {
     type Output = Self;
     fn mul(self, rhs: usize) -> Self::Output {
@@ -70,6 +70,13 @@ impl Mul<usize> for NodeCount {
     }
 }
 
+impl Add<usize> for NodeCount {
+    type Output = Self;
+    fn add(self, rhs: usize) -> Self::Output {
+        NodeCount(self.0 + rhs)
+    }
+}
+
 impl Div<usize> for NodeCount {
     type Output = Self;
     fn div(self, rhs: usize) -> Self::Output {
@@ -78,6 +85,11 @@ impl Div<usize> for NodeCount {
 }
 
 impl NodeCount {
+    /// The minimal number of nodes that must agree, that is more than 2/3 of all the nodes.
+    pub fn consensus_threshold(&self) -> NodeCount {
+        (*self * 2) / 3 + 1
+    }
+
     pub fn into_range(self) -> core::ops::Range<NodeIndex> {
         core::ops::Range {
             start: 0.into(),
@@ -200,6 +212,10 @@ impl NodeSubset {
         self.0.len()
     }
 
+    pub fn contains(&self, i: NodeIndex) -> bool {
+        self.0.get(i.0).unwrap_or(false)
+    }
+
     pub fn elements(&self) -> impl Iterator<Item = NodeIndex> + '_ {
         self.0
             .iter()
@@ -0,0 +1,24 @@
+use alloc::vec::Vec;
+use sp_core::crypto::KeyTypeId;
+
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"gran");
+
+mod app {
+    use sp_application_crypto::{app_crypto, ed25519};
+    app_crypto!(ed25519, super::KEY_TYPE);
+}
+
+sp_application_crypto::with_pair! {
+    pub type AuthorityPair = app::Pair;
+}
+pub type AuthoritySignature = app::Signature;
+pub type AuthorityId = app::Public;
+
+/// The weight of an authority
+pub type AuthorityWeight = u64;
+/// A list of GRANDPA authorities with associated weights
+pub type AuthorityList = Vec<(AuthorityId, AuthorityWeight)>;
+/// The monotonic identifier of a GRANDPA set of authorities
+pub type SetId = u64;
+/// The round indicator
+pub type RoundNumber = u64;
@@ -0,0 +1,60 @@
+/// Types lifted from [`finality_grandpa`](https://github.com/paritytech/finality-grandpa) and
+/// [`sc_consensus_grandpa::justification`](https://github.com/paritytech/polkadot-sdk/blob/master/substrate/client/consensus/grandpa/src/justification.rs),
+/// they keep the same encoding so justifications can be decoded as served by the node.
+use super::crypto::{AuthorityId, AuthoritySignature, RoundNumber, SetId};
+use crate::{hash_encoded, BlockNumber, Hash, Header, Proof};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// A precommit for a block and its ancestors.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct Precommit {
+    pub target_hash: Hash,
+    pub target_number: BlockNumber,
+}
+
+/// A signed precommit message.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct SignedPrecommit {
+    pub precommit: Precommit,
+    pub signature: AuthoritySignature,
+    pub id: AuthorityId,
+}
+
+/// A commit message which is an aggregate of precommits.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Hash, Debug, TypeInfo)]
+pub struct Commit {
+    pub target_hash: Hash,
+    pub target_number: BlockNumber,
+    pub precommits: Vec<SignedPrecommit>,
+}
+
+/// A GRANDPA justification for block finality, it includes a commit message and
+/// an ancestry proof including all headers routing all precommit target blocks to the commit target block.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct GrandpaJustification {
+    pub round: RoundNumber,
+    pub commit: Commit,
+    pub votes_ancestries: Vec<Header>,
+}
+
+impl core::hash::Hash for GrandpaJustification {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_encoded(self, state)
+    }
+}
+
+impl Proof for GrandpaJustification {}
+
+/// A message signed by GRANDPA voters, only precommits are needed to verify a justification.
+#[derive(Encode)]
+enum Message<'a> {
+    #[codec(index = 1)]
+    Precommit(&'a Precommit),
+}
+
+/// Encodes a precommit with the round and the set id, as it is signed by voters.
+pub fn localized_payload(round: RoundNumber, set_id: SetId, precommit: &Precommit) -> Vec<u8> {
+    (Message::Precommit(precommit), round, set_id).encode()
+}
@@ -0,0 +1,735 @@
+use crate::{
+    backend::{Backend, NativeBackend},
+    consensus::FinalityReport,
+    finality::types::{NodeCount, NodeIndex},
+    BlockNumber, ConsensusClient, ConsensusError, Hash, Header,
+};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use codec::{Decode, Encode};
+use crypto::{AuthorityList, SetId};
+use justification::{localized_payload, GrandpaJustification};
+use scale_info::TypeInfo;
+use sp_runtime::{ConsensusEngineId, DigestItem};
+
+pub mod crypto;
+pub mod justification;
+
+/// The `ConsensusEngineId` of GRANDPA.
+pub const GRANDPA_ENGINE_ID: ConsensusEngineId = *b"FRNK";
+
+/// A scheduled change of authority set (lifted from `sp_consensus_grandpa`)
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct ScheduledChange {
+    /// The new authorities after the change, along with their respective weights.
+    pub next_authorities: AuthorityList,
+    /// The number of blocks to delay.
+    pub delay: BlockNumber,
+}
+
+/// A consensus log item for GRANDPA (lifted from `sp_consensus_grandpa`)
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub enum ConsensusLog {
+    /// Schedule an authority set change, enacted once the block at `delay` blocks after the signal is finalized.
+    #[codec(index = 1)]
+    ScheduledChange(ScheduledChange),
+    /// Force an authority set change, signaled with the median last finalized block.
+    #[codec(index = 2)]
+    ForcedChange(BlockNumber, ScheduledChange),
+    /// Note that the authority with given index is disabled until the next change.
+    #[codec(index = 3)]
+    OnDisabled(u64),
+    /// A signal to pause the current authority set after the given delay.
+    #[codec(index = 4)]
+    Pause(BlockNumber),
+    /// A signal to resume the current authority set after the given delay.
+    #[codec(index = 5)]
+    Resume(BlockNumber),
+}
+
+/// An authority set change that has been signaled but not enacted yet
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct PendingChange {
+    pub next_authorities: AuthorityList,
+    /// Height of the block that enacts the change
+    pub effective_at: BlockNumber,
+    /// Only for forced changes, the median last finalized block when the change was signaled
+    pub median_finalized: Option<BlockNumber>,
+}
+
+impl PendingChange {
+    pub fn is_forced(&self) -> bool {
+        self.median_finalized.is_some()
+    }
+}
+
+/// The GRANDPA authority set trusted by the light client.
+/// It follows the `ScheduledChange` and `ForcedChange` digests of finalized headers.
+/// A scheduled change is enacted once the block at its effective height is finalized by the current set,
+/// so later blocks can not be verified before that. A forced change is enacted at its effective height regardless,
+/// blocks from that height on are finalized by the next set.
+/// Only changes signaled by headers finalized by the current set are followed, since nothing else proves that
+/// a signal belongs to the chain. Otherwise the set must be replaced by a trusted party, e.g. the sudo account of the chain.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct GrandpaAuthoritySet {
+    set_id: SetId,
+    authorities: AuthorityList,
+    pending_change: Option<PendingChange>,
+    /// Height of the last imported header, headers at or below it are not imported anymore
+    last_finalized: BlockNumber,
+}
+
+/// GRANDPA threshold, the weight of the honest authorities in the worst case (`total - (total - 1) / 3`).
+/// Authorities without weight can not finalize anything.
+fn threshold(authorities: &AuthorityList) -> Result<u64, ConsensusError> {
+    let total = authorities
+        .iter()
+        .fold(0u64, |total, (_, weight)| total.saturating_add(*weight));
+    if total == 0 {
+        return Err(ConsensusError::InvalidAuthorities);
+    }
+
+    Ok(total - (total - 1) / 3)
+}
+
+/// Reads the authority set change signaled by a GRANDPA log, if any
+fn signaled_change(log: &DigestItem) -> Option<ConsensusLog> {
+    match log {
+        DigestItem::Consensus(GRANDPA_ENGINE_ID, data) => {
+            // Other engines may share the same id, so items that are not GRANDPA logs are skipped
+            match ConsensusLog::decode(&mut &data[..]) {
+                Ok(change @ ConsensusLog::ScheduledChange(_))
+                | Ok(change @ ConsensusLog::ForcedChange(..)) => Some(change),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl GrandpaAuthoritySet {
+    pub fn new(set_id: SetId, authorities: AuthorityList) -> Self {
+        Self {
+            set_id,
+            authorities,
+            pending_change: None,
+            last_finalized: 0,
+        }
+    }
+
+    pub fn set_id(&self) -> SetId {
+        self.set_id
+    }
+
+    pub fn authorities(&self) -> &AuthorityList {
+        &self.authorities
+    }
+
+    pub fn pending_change(&self) -> Option<&PendingChange> {
+        self.pending_change.as_ref()
+    }
+
+    pub fn last_finalized(&self) -> BlockNumber {
+        self.last_finalized
+    }
+
+    /// GRANDPA threshold of the current set, it fails if the set has no weight
+    pub fn threshold(&self) -> Result<u64, ConsensusError> {
+        threshold(&self.authorities)
+    }
+
+    /// Set id and authorities that finalize the block at the given height
+    pub fn voters_at(
+        &self,
+        number: BlockNumber,
+    ) -> Result<(SetId, &AuthorityList), ConsensusError> {
+        match &self.pending_change {
+            Some(change) if change.is_forced() && number >= change.effective_at => {
+                Ok((self.set_id + 1, &change.next_authorities))
+            }
+            // The block that enacts a scheduled change must be imported first
+            Some(change) if !change.is_forced() && number > change.effective_at => {
+                Err(ConsensusError::PendingAuthoritySetChange)
+            }
+            _ => Ok((self.set_id, &self.authorities)),
+        }
+    }
+
+    /// Imports a header that has been finalized by the voters at its height.
+    /// Enacts the pending change, if due, and tracks the changes signaled in the header digest.
+    pub fn import_finalized_header(&mut self, header: &Header) -> Result<(), ConsensusError> {
+        if header.number <= self.last_finalized {
+            return Err(ConsensusError::StaleHeader);
+        }
+        self.voters_at(header.number)?;
+        self.enact_pending_change(header.number);
+
+        for log in header.digest.logs() {
+            if let Some(change) = signaled_change(log) {
+                self.signal_change(header.number, change)?;
+            }
+        }
+
+        // A change with no delay is enacted by the block that signals it
+        self.enact_pending_change(header.number);
+        self.last_finalized = header.number;
+        Ok(())
+    }
+
+    fn signal_change(
+        &mut self,
+        signal: BlockNumber,
+        change: ConsensusLog,
+    ) -> Result<(), ConsensusError> {
+        let (change, median_finalized) = match change {
+            ConsensusLog::ScheduledChange(change) => (change, None),
+            // Blocks up to the median are final, so it must be below the signal
+            ConsensusLog::ForcedChange(median, change) if median < signal => (change, Some(median)),
+            _ => return Err(ConsensusError::InvalidAuthoritySetChange),
+        };
+        // The next authorities must be able to finalize blocks
+        threshold(&change.next_authorities)?;
+
+        // Only one change at a time can be pending
+        if self.pending_change.is_some() {
+            return Err(ConsensusError::InvalidAuthoritySetChange);
+        }
+        let effective_at = signal
+            .checked_add(change.delay)
+            .ok_or(ConsensusError::InvalidAuthoritySetChange)?;
+
+        self.pending_change = Some(PendingChange {
+            next_authorities: change.next_authorities,
+            effective_at,
+            median_finalized,
+        });
+        Ok(())
+    }
+
+    fn enact_pending_change(&mut self, finalized: BlockNumber) {
+        let is_due = self
+            .pending_change
+            .as_ref()
+            .is_some_and(|change| change.effective_at <= finalized);
+
+        if is_due {
+            let change = self
+                .pending_change
+                .take()
+                .expect("The pending change has been checked to exist");
+            self.authorities = change.next_authorities;
+            self.set_id += 1;
+            if let Some(median) = change.median_finalized {
+                self.last_finalized = self.last_finalized.max(median);
+            }
+        }
+    }
+
+    /// Verifies that a justification finalizes the header, at its height
+    pub fn verify_header<B: Backend + ?Sized>(
+        &self,
+        header: &Header,
+        justification: &GrandpaJustification,
+        backend: &B,
+    ) -> Result<FinalityReport, ConsensusError> {
+        if justification.commit.target_number != header.number {
+            return Err(ConsensusError::InvalidCommitTarget);
+        }
+
+        self.verify_justification(&backend.hash_header(header), justification, backend)
+    }
+
+    /// Verifies that a justification finalizes the given block hash, by the voters at the height of the commit target.
+    /// Precommits must be signed by distinct voters, and they must target the commit target or its descendants.
+    pub fn verify_justification<B: Backend + ?Sized>(
+        &self,
+        block_hash: &Hash,
+        justification: &GrandpaJustification,
+        backend: &B,
+    ) -> Result<FinalityReport, ConsensusError> {
+        let commit = &justification.commit;
+        if commit.target_hash != *block_hash {
+            return Err(ConsensusError::InvalidCommitTarget);
+        }
+        let (set_id, authorities) = self.voters_at(commit.target_number)?;
+        let threshold = threshold(authorities)?;
+
+        let ancestries: BTreeMap<Hash, &Header> = justification
+            .votes_ancestries
+            .iter()
+            .map(|header| (backend.hash_header(header), header))
+            .collect();
+        let mut visited = BTreeSet::new();
+
+        let mut signers = Vec::new();
+        let mut achieved = 0u64;
+        for signed in commit.precommits.iter() {
+            let (node_index, weight) = authorities
+                .iter()
+                .enumerate()
+                .find(|(_, (id, _))| *id == signed.id)
+                .map(|(index, (_, weight))| (NodeIndex(index), *weight))
+                .ok_or(ConsensusError::MissingAuthorityKey)?;
+
+            if signers.contains(&node_index) {
+                return Err(ConsensusError::DuplicateNodeIndex);
+            }
+
+            let message = localized_payload(justification.round, set_id, &signed.precommit);
+            if !backend.verify_ed25519(signed.id.as_ref(), &message, signed.signature.as_ref()) {
+                return Err(ConsensusError::InvalidSignature);
+            }
+
+            // The precommit target must descend from the commit target, block by block,
+            // so that the height of the commit target is bound to the signed heights
+            let mut current = signed.precommit.target_hash;
+            let mut number = signed.precommit.target_number;
+            while current != commit.target_hash {
+                let header = ancestries
+                    .get(&current)
+                    .ok_or(ConsensusError::InvalidVoteAncestry)?;
+                if header.number != number || header.number <= commit.target_number {
+                    return Err(ConsensusError::InvalidVoteAncestry);
+                }
+                visited.insert(current);
+                current = header.parent_hash;
+                number -= 1;
+            }
+            if number != commit.target_number {
+                return Err(ConsensusError::InvalidCommitTarget);
+            }
+
+            signers.push(node_index);
+            achieved = achieved.saturating_add(weight);
+        }
+
+        // Unused headers would make the justification unnecessarily big
+        if visited.len() != ancestries.len() {
+            return Err(ConsensusError::InvalidVoteAncestry);
+        }
+
+        if achieved < threshold {
+            return Err(ConsensusError::NotEnoughSignatures);
+        }
+
+        let missing = (0..authorities.len())
+            .map(NodeIndex)
+            .filter(|node_index| !signers.contains(node_index))
+            .collect();
+
+        Ok(FinalityReport {
+            signers,
+            missing,
+            threshold: NodeCount(threshold as usize),
+            achieved: NodeCount(achieved as usize),
+        })
+    }
+}
+
+/// GRANDPA consensus client implementation.
+/// Threshold and achieved values of the finality report are authority weights.
+pub struct GrandpaConsensusClient<B = NativeBackend> {
+    authority_set: GrandpaAuthoritySet,
+    backend: B,
+}
+
+impl<B: Backend> ConsensusClient for GrandpaConsensusClient<B> {
+    type ConsensusProof = GrandpaJustification;
+
+    fn verify_consensus(
+        &self,
+        consensus_state: Hash,
+        proof: Self::ConsensusProof,
+    ) -> Result<FinalityReport, ConsensusError> {
+        self.authority_set
+            .verify_justification(&consensus_state, &proof, &self.backend)
+    }
+}
+
+impl GrandpaConsensusClient {
+    pub fn new(authority_set: GrandpaAuthoritySet) -> Self {
+        Self::with_backend(NativeBackend, authority_set)
+    }
+}
+
+impl<B: Backend> GrandpaConsensusClient<B> {
+    pub fn with_backend(backend: B, authority_set: GrandpaAuthoritySet) -> Self {
+        Self {
+            authority_set,
+            backend,
+        }
+    }
+
+    pub fn authority_set(&self) -> &GrandpaAuthoritySet {
+        &self.authority_set
+    }
+
+    /// Verifies that a justification finalizes the header, at its height
+    pub fn verify_header(
+        &self,
+        header: &Header,
+        justification: GrandpaJustification,
+    ) -> Result<FinalityReport, ConsensusError> {
+        self.authority_set
+            .verify_header(header, &justification, &self.backend)
+    }
+
+    /// Verifies the justification of a header and follows the authority set changes that it signals.
+    pub fn import_finalized_header(
+        &mut self,
+        header: &Header,
+        justification: GrandpaJustification,
+    ) -> Result<FinalityReport, ConsensusError> {
+        let report = self.verify_header(header, justification)?;
+        self.authority_set.import_finalized_header(header)?;
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grandpa::justification::{Commit, Precommit, SignedPrecommit};
+    use crypto::AuthorityPair;
+    use sp_core::Pair;
+    use sp_runtime::{traits::Header as HeaderT, Digest};
+
+    fn header(number: BlockNumber, parent_hash: Hash, digest: Digest) -> Header {
+        Header::new(number, Hash::zero(), Hash::zero(), parent_hash, digest)
+    }
+
+    fn justification(
+        pairs: &[AuthorityPair],
+        set_id: SetId,
+        target: &Header,
+        precommit_target: &Header,
+        votes_ancestries: Vec<Header>,
+    ) -> GrandpaJustification {
+        let round = 7;
+        let precommit = Precommit {
+            target_hash: precommit_target.hash(),
+            target_number: precommit_target.number,
+        };
+        let precommits = pairs
+            .iter()
+            .map(|pair| SignedPrecommit {
+                precommit: precommit.clone(),
+                signature: pair.sign(&localized_payload(round, set_id, &precommit)),
+                id: pair.public(),
+            })
+            .collect();
+
+        GrandpaJustification {
+            round,
+            commit: Commit {
+                target_hash: target.hash(),
+                target_number: target.number,
+                precommits,
+            },
+            votes_ancestries,
+        }
+    }
+
+    fn authorities(pairs: &[AuthorityPair]) -> AuthorityList {
+        pairs.iter().map(|pair| (pair.public(), 1)).collect()
+    }
+
+    #[test]
+    fn verifies_justification_with_vote_ancestry() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let set = GrandpaAuthoritySet::new(0, authorities(&pairs));
+        let target = header(10, Hash::zero(), Digest::default());
+        let descendant = header(11, target.hash(), Digest::default());
+
+        let proof = justification(
+            &pairs[..3],
+            0,
+            &target,
+            &descendant,
+            vec![descendant.clone()],
+        );
+        let report = set
+            .verify_justification(&target.hash(), &proof, &NativeBackend)
+            .unwrap();
+        assert_eq!(report.signers.len(), 3);
+        assert_eq!(report.missing, vec![NodeIndex(3)]);
+        assert_eq!(report.threshold, NodeCount(3));
+
+        // Missing ancestry
+        let proof = justification(&pairs[..3], 0, &target, &descendant, Vec::new());
+        assert!(matches!(
+            set.verify_justification(&target.hash(), &proof, &NativeBackend),
+            Err(ConsensusError::InvalidVoteAncestry)
+        ));
+
+        // Signed for another set id
+        let proof = justification(&pairs[..3], 1, &target, &target, Vec::new());
+        assert!(matches!(
+            set.verify_justification(&target.hash(), &proof, &NativeBackend),
+            Err(ConsensusError::InvalidSignature)
+        ));
+
+        let proof = justification(&pairs[..2], 0, &target, &target, Vec::new());
+        assert!(matches!(
+            set.verify_justification(&target.hash(), &proof, &NativeBackend),
+            Err(ConsensusError::NotEnoughSignatures)
+        ));
+    }
+
+    #[test]
+    fn follows_scheduled_changes() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let next_pairs: Vec<AuthorityPair> = (0..3).map(|_| AuthorityPair::generate().0).collect();
+        let mut client =
+            GrandpaConsensusClient::new(GrandpaAuthoritySet::new(0, authorities(&pairs)));
+
+        let change = ConsensusLog::ScheduledChange(ScheduledChange {
+            next_authorities: authorities(&next_pairs),
+            delay: 2,
+        });
+        let signal = header(
+            10,
+            Hash::zero(),
+            Digest {
+                logs: vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())],
+            },
+        );
+        let proof = justification(&pairs, 0, &signal, &signal, Vec::new());
+        client.import_finalized_header(&signal, proof).unwrap();
+        assert_eq!(client.authority_set().set_id(), 0);
+        assert_eq!(
+            client
+                .authority_set()
+                .pending_change()
+                .unwrap()
+                .effective_at,
+            12
+        );
+
+        // The enacting block is finalized by the current set
+        let enacting = header(12, Hash::repeat_byte(1), Digest::default());
+        let proof = justification(&pairs, 0, &enacting, &enacting, Vec::new());
+        client.import_finalized_header(&enacting, proof).unwrap();
+        assert_eq!(client.authority_set().set_id(), 1);
+        assert_eq!(
+            client.authority_set().authorities(),
+            &authorities(&next_pairs)
+        );
+
+        // The next blocks are finalized by the new set
+        let next = header(13, enacting.hash(), Digest::default());
+        let proof = justification(&pairs, 0, &next, &next, Vec::new());
+        assert!(client.import_finalized_header(&next, proof).is_err());
+        let proof = justification(&next_pairs, 1, &next, &next, Vec::new());
+        assert!(client.import_finalized_header(&next, proof).is_ok());
+    }
+
+    fn change_digest(change: ConsensusLog) -> Digest {
+        Digest {
+            logs: vec![DigestItem::Consensus(GRANDPA_ENGINE_ID, change.encode())],
+        }
+    }
+
+    #[test]
+    fn scheduled_changes_must_be_enacted_in_order() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let next_pairs: Vec<AuthorityPair> = (0..3).map(|_| AuthorityPair::generate().0).collect();
+        let mut client =
+            GrandpaConsensusClient::new(GrandpaAuthoritySet::new(0, authorities(&pairs)));
+
+        let signal = header(
+            10,
+            Hash::zero(),
+            change_digest(ConsensusLog::ScheduledChange(ScheduledChange {
+                next_authorities: authorities(&next_pairs),
+                delay: 2,
+            })),
+        );
+        let proof = justification(&pairs, 0, &signal, &signal, Vec::new());
+        client.import_finalized_header(&signal, proof).unwrap();
+
+        // Blocks past the change can not be finalized by the current set, nor verified against the next one yet
+        let skipping = header(13, Hash::repeat_byte(1), Digest::default());
+        let proof = justification(&pairs, 0, &skipping, &skipping, Vec::new());
+        assert!(matches!(
+            client.import_finalized_header(&skipping, proof),
+            Err(ConsensusError::PendingAuthoritySetChange)
+        ));
+        let proof = justification(&next_pairs, 1, &skipping, &skipping, Vec::new());
+        assert!(matches!(
+            client.verify_header(&skipping, proof),
+            Err(ConsensusError::PendingAuthoritySetChange)
+        ));
+        assert_eq!(client.authority_set().set_id(), 0);
+
+        // The commit target height is bound to the header
+        let enacting = header(12, Hash::repeat_byte(1), Digest::default());
+        let mut proof = justification(&pairs, 0, &enacting, &enacting, Vec::new());
+        proof.commit.target_number = 11;
+        assert!(matches!(
+            client.verify_header(&enacting, proof),
+            Err(ConsensusError::InvalidCommitTarget)
+        ));
+
+        // Headers at or below the last imported one are stale
+        let stale = header(10, Hash::repeat_byte(2), Digest::default());
+        let proof = justification(&pairs, 0, &stale, &stale, Vec::new());
+        assert!(matches!(
+            client.import_finalized_header(&stale, proof),
+            Err(ConsensusError::StaleHeader)
+        ));
+    }
+
+    #[test]
+    fn forced_changes_are_followed_only_once_finalized() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let forged_pairs: Vec<AuthorityPair> =
+            (0..3).map(|_| AuthorityPair::generate().0).collect();
+        let mut client =
+            GrandpaConsensusClient::new(GrandpaAuthoritySet::new(0, authorities(&pairs)));
+        let last = header(10, Hash::zero(), Digest::default());
+        let proof = justification(&pairs, 0, &last, &last, Vec::new());
+        client.import_finalized_header(&last, proof).unwrap();
+
+        // Anyone can build a signal on top of the last finalized header, along with a child finalized by the set it names
+        let signal = header(
+            11,
+            last.hash(),
+            change_digest(ConsensusLog::ForcedChange(
+                10,
+                ScheduledChange {
+                    next_authorities: authorities(&forged_pairs),
+                    delay: 0,
+                },
+            )),
+        );
+        let child = header(12, signal.hash(), Digest::default());
+        let proof = justification(&forged_pairs, 1, &child, &child, Vec::new());
+        assert!(matches!(
+            client.import_finalized_header(&child, proof),
+            Err(ConsensusError::MissingAuthorityKey)
+        ));
+        let proof = justification(&forged_pairs, 1, &signal, &child, vec![child.clone()]);
+        assert!(matches!(
+            client.import_finalized_header(&signal, proof),
+            Err(ConsensusError::MissingAuthorityKey)
+        ));
+        assert_eq!(client.authority_set().set_id(), 0);
+        assert_eq!(client.authority_set().authorities(), &authorities(&pairs));
+        assert!(client.authority_set().pending_change().is_none());
+        assert_eq!(client.authority_set().last_finalized(), 10);
+
+        // The signal is followed once the current set finalizes it, a change with no delay is enacted right away
+        let proof = justification(&pairs, 0, &signal, &signal, Vec::new());
+        client.import_finalized_header(&signal, proof).unwrap();
+        assert_eq!(client.authority_set().set_id(), 1);
+        assert_eq!(
+            client.authority_set().authorities(),
+            &authorities(&forged_pairs)
+        );
+        let proof = justification(&forged_pairs, 1, &child, &child, Vec::new());
+        client.import_finalized_header(&child, proof).unwrap();
+        assert_eq!(client.authority_set().last_finalized(), 12);
+    }
+
+    #[test]
+    fn authorities_without_weight_finalize_nothing() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let target = header(10, Hash::zero(), Digest::default());
+        let proof = justification(&[], 0, &target, &target, Vec::new());
+
+        let empty = GrandpaAuthoritySet::new(0, AuthorityList::new());
+        assert!(matches!(
+            empty.threshold(),
+            Err(ConsensusError::InvalidAuthorities)
+        ));
+        assert!(matches!(
+            empty.verify_header(&target, &proof, &NativeBackend),
+            Err(ConsensusError::InvalidAuthorities)
+        ));
+        let weightless: AuthorityList = pairs.iter().map(|pair| (pair.public(), 0)).collect();
+        let weightless = GrandpaAuthoritySet::new(0, weightless);
+        assert!(matches!(
+            weightless.verify_header(&target, &proof, &NativeBackend),
+            Err(ConsensusError::InvalidAuthorities)
+        ));
+
+        // Changes to authorities without weight are rejected
+        let mut set = GrandpaAuthoritySet::new(0, authorities(&pairs));
+        assert_eq!(set.threshold().unwrap(), 3);
+        let empty_change = ScheduledChange {
+            next_authorities: AuthorityList::new(),
+            delay: 2,
+        };
+        for change in [
+            ConsensusLog::ScheduledChange(empty_change.clone()),
+            ConsensusLog::ForcedChange(8, empty_change),
+        ] {
+            let signal = header(10, Hash::zero(), change_digest(change));
+            assert!(matches!(
+                set.import_finalized_header(&signal),
+                Err(ConsensusError::InvalidAuthorities)
+            ));
+        }
+        assert!(set.pending_change().is_none());
+    }
+
+    #[test]
+    fn finalized_forced_signals_hand_over_at_the_effective_height() {
+        let pairs: Vec<AuthorityPair> = (0..4).map(|_| AuthorityPair::generate().0).collect();
+        let next_pairs: Vec<AuthorityPair> = (0..3).map(|_| AuthorityPair::generate().0).collect();
+        let mut client =
+            GrandpaConsensusClient::new(GrandpaAuthoritySet::new(0, authorities(&pairs)));
+
+        let signal = header(
+            10,
+            Hash::zero(),
+            change_digest(ConsensusLog::ForcedChange(
+                8,
+                ScheduledChange {
+                    next_authorities: authorities(&next_pairs),
+                    delay: 2,
+                },
+            )),
+        );
+        let proof = justification(&pairs, 0, &signal, &signal, Vec::new());
+        client.import_finalized_header(&signal, proof).unwrap();
+        assert_eq!(
+            client
+                .authority_set()
+                .pending_change()
+                .unwrap()
+                .median_finalized,
+            Some(8)
+        );
+
+        // From the effective height on, blocks are finalized by the next set
+        let effective = header(12, Hash::repeat_byte(1), Digest::default());
+        let proof = justification(&pairs, 0, &effective, &effective, Vec::new());
+        assert!(client.verify_header(&effective, proof).is_err());
+        let proof = justification(&next_pairs, 1, &effective, &effective, Vec::new());
+        client.import_finalized_header(&effective, proof).unwrap();
+        assert_eq!(client.authority_set().set_id(), 1);
+
+        // A forced change must be signaled above its median finalized block
+        let mut set = GrandpaAuthoritySet::new(0, authorities(&pairs));
+        let invalid = header(
+            10,
+            Hash::zero(),
+            change_digest(ConsensusLog::ForcedChange(
+                10,
+                ScheduledChange {
+                    next_authorities: authorities(&next_pairs),
+                    delay: 2,
+                },
+            )),
+        );
+        assert!(matches!(
+            set.import_finalized_header(&invalid),
+            Err(ConsensusError::InvalidAuthoritySetChange)
+        ));
+    }
+}
@@ -2,9 +2,13 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
+pub use ancestry::{AncestryProof, VerifiedAncestor};
 use codec::{Decode, Encode};
+use consensus::FinalityReport;
 use finality::types::NodeIndex;
 use ink_env::hash::{Blake2x256 as InkBlakeTwo256, CryptoHash, Keccak256 as InkKeccak256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use sp_core::Hasher;
 use sp_runtime::{
     generic,
@@ -12,11 +16,26 @@ use sp_runtime::{
 };
 pub use state::GetCommitmentResponseProof;
 pub use state::GetResponse;
+pub use state::{GetTimestampResponseProof, VerifiedBlock};
 
 // Remember to make all these not public and only expose what is needed
+pub mod ancestry;
+pub mod aura;
+pub mod authority_set;
+pub mod backend;
+pub mod beefy;
 pub mod consensus;
 pub mod finality;
+pub mod grandpa;
+pub mod merkle;
+pub mod misbehaviour;
+pub mod parachain;
+pub mod session;
 pub mod state;
+pub mod store;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod version;
 
 #[derive(Encode, Decode, Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub struct AccountId([u8; 32]);
@@ -37,6 +56,8 @@ pub enum StateProofError {
     MissingValueError,
     FirstKeyValueError,
     InvalidCommitId,
+    StateRootMismatch,
+    InvalidTimestamp,
 }
 
 #[derive(Debug)]
@@ -44,11 +65,47 @@ pub enum ConsensusError {
     InvalidAuthorities,
     MissingAuthorityKey,
     InvalidSignature,
+    /// Signatures of the given authorities are not valid
+    InvalidSigners(Vec<NodeIndex>),
+    /// The batch of signatures does not hold, though every signature is valid on its own
+    InconsistentBatchVerification,
     InvalidAlephLogPreRuntime,
     InvalidAlephLogSeal,
+    InvalidDigest,
+    InvalidSignatureSetSize,
+    DuplicateNodeIndex,
+    NotEnoughSignatures,
+    InvalidJustification,
+    InvalidCommitTarget,
+    InvalidVoteAncestry,
+    InvalidAuthoritySetChange,
+    InvalidHeadProof,
+    ParachainHeaderMismatch,
+    UnknownValidatorSet,
+    InvalidValidatorProof,
+    MissingMmrRoot,
+    InvalidMmrProof,
+    EmergencyFinalityRejected,
+    EmergencyFinalityUnconfirmed,
+    InvalidMisbehaviour,
+    InvalidAuraSlot,
+    MissingAuraSeal,
+    InvalidAuraSeal,
+    /// An authority is not at the claimed position of the committed authority set
+    InvalidAuthorityProof,
+    /// The header is not above the last imported one
+    StaleHeader,
+    /// The header is past a scheduled authority set change that has not been enacted yet
+    PendingAuthoritySetChange,
 }
 pub trait Proof: 'static + Send + Sync + Sized + Clone + Eq + PartialEq + core::hash::Hash {}
 
+/// Hashes the SCALE encoding of a value, for proofs that carry headers.
+/// Headers do not implement `Hash`, so the encoding is hashed instead.
+pub(crate) fn hash_encoded<T: Encode, H: core::hash::Hasher>(value: &T, state: &mut H) {
+    core::hash::Hash::hash(&value.encode(), state)
+}
+
 pub trait ConsensusClient {
     type ConsensusProof: Proof;
 
@@ -57,7 +114,7 @@ pub trait ConsensusClient {
         &self,
         consensus_state: Hash,
         proof: Self::ConsensusProof,
-    ) -> Result<Vec<NodeIndex>, ConsensusError>;
+    ) -> Result<FinalityReport, ConsensusError>;
 }
 
 pub type FinalityVersion = u32;
@@ -125,15 +182,24 @@ mod test {
     };
 
     use crate::{
-        consensus::{AlephConsensusLogBuilder, PhatContractConsensusClient},
+        backend::{HeaderHasher, NativeBackend, PinkBackend, SignatureVerifier, SignedMessage},
+        consensus::{
+            decode_digest, digest_from_logs, AlephConsensusClient, AlephConsensusLogBuilder,
+            EmergencyConfirmation, EmergencyFinalityPolicy, Finality, FinalityThreshold,
+        },
         finality::{
-            crypto::{verify, AuthorityId, AuthoritySignature},
+            crypto::{verify, AlephSignature, AuthorityId, AuthorityPair, AuthoritySignature},
             justification,
+            types::{AlephNodeIndex, NodeCount, NodeIndex},
         },
-        state::GetResponse,
-        BlockHash, ConsensusClient, GetSingleState, Hash,
+        state::{commitment_entry, GetResponse, GetTimestampResponseProof},
+        test_utils::{self, MockCommittee},
+        AncestryProof, BlockHash, ConsensusClient, ConsensusError, GetCommitmentResponseProof,
+        GetSingleState, Hash, Header, StateProofError,
     };
     use pink_extension::chain_extension::mock as pink_mock;
+    use sp_core::Pair;
+    use sp_runtime::DigestItem;
 
     #[test]
     fn verify_consensus() {
@@ -141,7 +207,7 @@ mod test {
             let auth_public =
                 AuthorityId::try_from(pubkey).expect("Expected to be a valid public key");
             let signature = AuthoritySignature::try_from(sign).expect("Expected a valid signature");
-            verify(&auth_public, msg, &signature.into())
+            verify(&NativeBackend, &auth_public, msg, &signature.into())
         });
 
         // Data has been taken directly from the Chain
@@ -166,8 +232,11 @@ mod test {
             authority_keys.push(AuthorityId::try_from(pub_key.as_slice()).unwrap())
         });
 
-        let consensus_client =
-            PhatContractConsensusClient::new(authority_keys.clone(), emergency_finalizer.clone());
+        let consensus_client = AlephConsensusClient::with_backend(
+            PinkBackend,
+            authority_keys.clone(),
+            emergency_finalizer.clone(),
+        );
 
         match justification {
             justification::AlephJustification::CommitteeMultisignature(signatures) => {
@@ -198,13 +267,23 @@ mod test {
                     digest,
                 );
 
-                let nodes_verify = consensus_client.verify_consensus(consensus_state, signatures);
-
-                if nodes_verify.is_ok() {
-                    assert!(true)
-                } else {
-                    assert!(false)
-                }
+                let report = consensus_client
+                    .verify_consensus(consensus_state, signatures.clone())
+                    .unwrap();
+                assert_eq!(
+                    report.signers,
+                    vec![NodeIndex(0), NodeIndex(2), NodeIndex(3)]
+                );
+                assert_eq!(report.missing, vec![NodeIndex(1)]);
+                assert_eq!(report.threshold, NodeCount(3));
+                assert_eq!(report.achieved, NodeCount(3));
+
+                // Three signatures out of four are not enough when all authorities are required
+                let consensus_client = consensus_client.with_threshold(FinalityThreshold::Fixed(4));
+                assert!(matches!(
+                    consensus_client.verify_consensus(consensus_state, signatures),
+                    Err(ConsensusError::NotEnoughSignatures)
+                ));
             }
             _ => assert!(false),
         }
@@ -259,4 +338,292 @@ mod test {
             )
         )
     }
+
+    #[test]
+    fn digest_from_encoded_logs() {
+        // Digest logs as returned by the `chain_getBlock` rpc
+        let logs = vec![
+            Vec::from_hex("066175726120f88e5ee100000000").unwrap(),
+            Vec::from_hex("056175726101018ae5c8c9449bb4dcce12dcb52ae0ac10b4718a5c5a67a99244e77e5598899d1efc6b40e8c0b2bf9d6527ebd260dfff7e1f7d9fda2209a9623c238774126b198e").unwrap(),
+        ];
+
+        let digest = digest_from_logs(&logs).unwrap();
+        let aura_digest = AlephConsensusLogBuilder::logs("f88e5ee100000000", "8ae5c8c9449bb4dcce12dcb52ae0ac10b4718a5c5a67a99244e77e5598899d1efc6b40e8c0b2bf9d6527ebd260dfff7e1f7d9fda2209a9623c238774126b198e").unwrap().build();
+        assert_eq!(digest, aura_digest);
+        assert_eq!(decode_digest(&digest.encode()).unwrap(), digest);
+
+        // Any other digest item is supported as well
+        let mut digest = digest;
+        digest.push(DigestItem::RuntimeEnvironmentUpdated);
+        assert_eq!(decode_digest(&digest.encode()).unwrap(), digest);
+        assert!(decode_digest(&logs[0]).is_err());
+    }
+
+    #[test]
+    fn finality_threshold_requires_supermajority() {
+        assert_eq!(
+            FinalityThreshold::Supermajority.required(NodeCount(4)),
+            NodeCount(3)
+        );
+        assert_eq!(
+            FinalityThreshold::Supermajority.required(NodeCount(10)),
+            NodeCount(7)
+        );
+        assert_eq!(
+            FinalityThreshold::Fraction {
+                numerator: 9,
+                denominator: 10
+            }
+            .required(NodeCount(10)),
+            NodeCount(10)
+        );
+        assert_eq!(
+            FinalityThreshold::Fixed(4).required(NodeCount(4)),
+            NodeCount(4)
+        );
+
+        // Weaker policies are raised to a supermajority
+        assert_eq!(
+            FinalityThreshold::Fraction {
+                numerator: 1,
+                denominator: 2
+            }
+            .required(NodeCount(10)),
+            NodeCount(7)
+        );
+        assert_eq!(
+            FinalityThreshold::Fixed(0).required(NodeCount(4)),
+            NodeCount(3)
+        );
+        assert_eq!(
+            FinalityThreshold::Fixed(1).required(NodeCount(4)),
+            NodeCount(3)
+        );
+
+        // Large fractions do not overflow
+        assert_eq!(
+            FinalityThreshold::Fraction {
+                numerator: u32::MAX,
+                denominator: u32::MAX
+            }
+            .required(NodeCount(1 << 20)),
+            NodeCount((1 << 20) + 1)
+        );
+    }
+
+    #[test]
+    fn timestamp_proof_rejects_other_keys() {
+        let root = StateCommitment {
+            timestamp: 123456789,
+            state_root: Hash::zero(),
+        };
+        let proof = Proof {
+            height: 1,
+            proof: Vec::new(),
+        };
+        let keys =
+            vec![
+                Vec::from_hex("5c0d1176a568c1f92944340dbfed9e9c530ebca703c85910e7164cb7d1c9e47b")
+                    .unwrap(),
+            ];
+
+        assert_eq!(
+            GetTimestampResponseProof::new(GetResponseProof::new(&keys, &root, &proof)),
+            Err(StateProofError::InvalidKeysError)
+        );
+    }
+
+    #[test]
+    fn commitment_proof_is_keyed_by_commit_id() {
+        let root = StateCommitment {
+            timestamp: 123456789,
+            state_root: Hash::zero(),
+        };
+        let proof = Proof {
+            height: 1,
+            proof: Vec::new(),
+        };
+        let id = Hash::repeat_byte(1);
+        let key = commitment_entry(&id).key().to_vec();
+
+        assert!(GetCommitmentResponseProof::new(
+            1,
+            id,
+            GetResponseProof::new(&[key.clone()], &root, &proof)
+        )
+        .is_ok());
+        assert_eq!(
+            GetCommitmentResponseProof::new(
+                1,
+                Hash::repeat_byte(2),
+                GetResponseProof::new(&[key], &root, &proof)
+            ),
+            Err(StateProofError::InvalidKeysError)
+        );
+
+        // Pallet layout: commit and metadata, iv, revealed key
+        let stored = ((vec![1u8, 2], vec![3u8]), vec![4u8; 12], vec![5u8; 32]).encode();
+        let commitment = commitment_entry(&id).decode(&stored).unwrap();
+        assert_eq!(commitment.value(), &[1, 2]);
+        assert_eq!(commitment.metadata(), &[3]);
+        assert_eq!(commitment.nonce(), &[4; 12]);
+        assert!(commitment.is_revealed());
+    }
+
+    #[test]
+    fn emergency_finality_follows_policy() {
+        let emergency_finalizer = AuthorityPair::generate().0;
+        let block_hash = Hash::repeat_byte(1);
+        let emergency_justification = || {
+            justification::AlephJustification::EmergencySignature(
+                emergency_finalizer.sign(block_hash.as_bytes()),
+            )
+        };
+
+        let client =
+            AlephConsensusClient::new(Vec::<AuthorityId>::new(), emergency_finalizer.public());
+        assert!(matches!(
+            client.verify_justification(10, block_hash, emergency_justification(), None),
+            Err(ConsensusError::EmergencyFinalityRejected)
+        ));
+
+        let client =
+            client.with_emergency_policy(EmergencyFinalityPolicy::BlockRanges(vec![(5, 10)]));
+        assert_eq!(
+            client
+                .verify_justification(10, block_hash, emergency_justification(), None)
+                .unwrap(),
+            Finality::Emergency { confirmed_by: None }
+        );
+        assert!(matches!(
+            client.verify_justification(11, block_hash, emergency_justification(), None),
+            Err(ConsensusError::EmergencyFinalityRejected)
+        ));
+
+        let client =
+            client.with_emergency_policy(EmergencyFinalityPolicy::RequireCommitteeConfirmation);
+        assert!(matches!(
+            client.verify_justification(10, block_hash, emergency_justification(), None),
+            Err(ConsensusError::EmergencyFinalityUnconfirmed)
+        ));
+    }
+
+    #[test]
+    fn emergency_finality_is_confirmed_by_a_committee_descendant() {
+        let committee = MockCommittee::new(1, 4);
+        let finalizer = MockCommittee::new(2, 1);
+        let client =
+            AlephConsensusClient::new(committee.authorities(), finalizer.authorities()[0].clone())
+                .with_emergency_policy(EmergencyFinalityPolicy::RequireCommitteeConfirmation);
+
+        let genesis = test_utils::header(0, Hash::zero(), Hash::zero(), Vec::new());
+        let chain = test_utils::descendants(&genesis, 12);
+        let block = &chain[9];
+        let block_hash = NativeBackend.hash_header(block);
+        let emergency_justification =
+            || test_utils::emergency_justification(&finalizer.pairs()[0], block);
+        // Confirmation by the committee of the given header, ancestors go from its parent down
+        let confirmation = |header: &Header, ancestors: Vec<Header>| EmergencyConfirmation {
+            justification: committee.sign(&NativeBackend.hash_header(header)),
+            header: header.clone(),
+            ancestry: AncestryProof::new(ancestors),
+        };
+
+        let confirmed = confirmation(&chain[11], vec![chain[10].clone(), chain[9].clone()]);
+        assert_eq!(
+            client
+                .verify_justification(10, block_hash, emergency_justification(), Some(confirmed))
+                .unwrap(),
+            Finality::Emergency {
+                confirmed_by: Some(12)
+            }
+        );
+
+        // The confirming block must be finalized by the committee
+        let mut unfinalized = confirmation(&chain[11], vec![chain[10].clone(), chain[9].clone()]);
+        unfinalized.justification =
+            MockCommittee::new(3, 4).sign(&NativeBackend.hash_header(&chain[11]));
+        assert!(client
+            .verify_justification(10, block_hash, emergency_justification(), Some(unfinalized))
+            .is_err());
+
+        // An earlier block does not confirm it
+        let earlier = confirmation(&chain[8], vec![chain[7].clone()]);
+        assert!(matches!(
+            client.verify_justification(10, block_hash, emergency_justification(), Some(earlier)),
+            Err(ConsensusError::EmergencyFinalityUnconfirmed)
+        ));
+
+        // Nor does a block of another fork
+        let fork = test_utils::header(
+            10,
+            NativeBackend.hash_header(&chain[8]),
+            Hash::repeat_byte(1),
+            Vec::new(),
+        );
+        let fork_chain = test_utils::descendants(&fork, 2);
+        let forked = confirmation(&fork_chain[1], vec![fork_chain[0].clone(), fork]);
+        assert!(matches!(
+            client.verify_justification(10, block_hash, emergency_justification(), Some(forked)),
+            Err(ConsensusError::EmergencyFinalityUnconfirmed)
+        ));
+    }
+
+    #[test]
+    fn batch_verification_pinpoints_invalid_signers() {
+        let committee = MockCommittee::new(1, 4);
+        let block_hash = Hash::repeat_byte(1);
+        let client =
+            AlephConsensusClient::new(committee.authorities(), committee.authorities()[0].clone());
+
+        let report = client
+            .verify_consensus(block_hash, committee.sign(&block_hash))
+            .unwrap();
+        assert_eq!(report.achieved, NodeCount(4));
+
+        // The second authority signs another block
+        let mut signatures = committee.sign(&block_hash);
+        let wrong_sign = AlephSignature::from(committee.pairs()[1].sign(Hash::zero().as_bytes()));
+        signatures = signatures.add_signature(&wrong_sign, AlephNodeIndex(1));
+        match client.verify_consensus(block_hash, signatures) {
+            Err(ConsensusError::InvalidSigners(signers)) => assert_eq!(signers, vec![NodeIndex(1)]),
+            _ => panic!("Expected the second authority to be an invalid signer"),
+        }
+    }
+
+    /// Backend whose batch verification never holds, unlike the verification of single signatures
+    struct RejectingBatches;
+
+    impl SignatureVerifier for RejectingBatches {
+        fn verify_ed25519(&self, public: &[u8], message: &[u8], signature: &[u8]) -> bool {
+            NativeBackend.verify_ed25519(public, message, signature)
+        }
+
+        fn verify_ed25519_batch(&self, _batch: &[SignedMessage]) -> bool {
+            false
+        }
+    }
+
+    impl HeaderHasher for RejectingBatches {
+        fn hash_header(&self, header: &Header) -> Hash {
+            NativeBackend.hash_header(header)
+        }
+    }
+
+    #[test]
+    fn inconsistent_batch_verification_is_rejected() {
+        let committee = MockCommittee::new(1, 4);
+        let finalizer = MockCommittee::new(2, 1);
+        let client = AlephConsensusClient::with_backend(
+            RejectingBatches,
+            committee.authorities(),
+            finalizer.authorities()[0].clone(),
+        );
+        let block_hash = Hash::repeat_byte(1);
+
+        assert!(matches!(
+            client.verify_consensus(block_hash, committee.sign(&block_hash)),
+            Err(ConsensusError::InconsistentBatchVerification)
+        ));
+    }
 }
@@ -0,0 +1,145 @@
+/// Binary Merkle trees, compatible with the ones built by `binary_merkle_tree`.
+/// Leaves are hashed, and at each level the last node is promoted as it is when it has no sibling.
+use crate::Hash;
+use alloc::vec::Vec;
+use sp_core::Hasher;
+
+/// Computes the root of a binary Merkle tree. The root of an empty tree is zero.
+pub fn merkle_root<H, L>(leaves: &[L]) -> Hash
+where
+    H: Hasher<Out = Hash>,
+    L: AsRef<[u8]>,
+{
+    let mut level: Vec<Hash> = leaves.iter().map(|leaf| H::hash(leaf.as_ref())).collect();
+    if level.is_empty() {
+        return Hash::zero();
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merge::<H>(left, right),
+                [promoted] => *promoted,
+                _ => unreachable!("Chunks have at most two nodes"),
+            })
+            .collect();
+    }
+
+    level[0]
+}
+
+/// Builds the proof of a leaf, that is the siblings of the nodes from the leaf up to the root.
+pub fn merkle_proof<H, L>(leaves: &[L], leaf_index: usize) -> Option<Vec<Hash>>
+where
+    H: Hasher<Out = Hash>,
+    L: AsRef<[u8]>,
+{
+    if leaf_index >= leaves.len() {
+        return None;
+    }
+
+    let mut level: Vec<Hash> = leaves.iter().map(|leaf| H::hash(leaf.as_ref())).collect();
+    let mut position = leaf_index;
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        let sibling = position ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merge::<H>(left, right),
+                [promoted] => *promoted,
+                _ => unreachable!("Chunks have at most two nodes"),
+            })
+            .collect();
+        position /= 2;
+    }
+
+    Some(proof)
+}
+
+/// Verifies that a leaf is at a given index of a tree with the given root and amount of leaves.
+pub fn verify_merkle_proof<H>(
+    root: &Hash,
+    proof: &[Hash],
+    number_of_leaves: usize,
+    leaf_index: usize,
+    leaf: &[u8],
+) -> bool
+where
+    H: Hasher<Out = Hash>,
+{
+    if leaf_index >= number_of_leaves {
+        return false;
+    }
+
+    let mut proof = proof.iter();
+    let mut computed = H::hash(leaf);
+    let mut position = leaf_index;
+    let mut width = number_of_leaves;
+    while width > 1 {
+        // The last node of a level with an odd width is promoted
+        let is_promoted = position + 1 == width && width % 2 == 1;
+        if !is_promoted {
+            let Some(sibling) = proof.next() else {
+                return false;
+            };
+            computed = if position % 2 == 1 {
+                merge::<H>(sibling, &computed)
+            } else {
+                merge::<H>(&computed, sibling)
+            };
+        }
+
+        position /= 2;
+        width = (width + 1) / 2;
+    }
+
+    proof.next().is_none() && computed == *root
+}
+
+fn merge<H: Hasher<Out = Hash>>(left: &Hash, right: &Hash) -> Hash {
+    let mut combined = [0u8; 64];
+    combined[..32].copy_from_slice(left.as_bytes());
+    combined[32..].copy_from_slice(right.as_bytes());
+    H::hash(&combined)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ContractKeccak256;
+
+    #[test]
+    fn verifies_proofs_of_every_leaf() {
+        for count in 1..10u8 {
+            let leaves: Vec<[u8; 20]> = (0..count).map(|i| [i; 20]).collect();
+            let root = merkle_root::<ContractKeccak256, _>(&leaves);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof::<ContractKeccak256, _>(&leaves, index).unwrap();
+                assert!(verify_merkle_proof::<ContractKeccak256>(
+                    &root,
+                    &proof,
+                    leaves.len(),
+                    index,
+                    leaf
+                ));
+                // The same proof does not hold for another index
+                if leaves.len() > 1 {
+                    assert!(!verify_merkle_proof::<ContractKeccak256>(
+                        &root,
+                        &proof,
+                        leaves.len(),
+                        (index + 1) % leaves.len(),
+                        leaf
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    authority_set::AuthorityKeys,
+    backend::{Backend, HeaderHasher},
+    consensus::{verify_signature_set, FinalityThreshold},
+    finality::crypto::{AlephSignature, AlephSignatureSet},
+    BlockNumber, ConsensusError, Hash, Header,
+};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// A header along with the committee justification that finalizes it
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct FinalizedHeader {
+    pub header: Header,
+    pub justification: AlephSignatureSet<AlephSignature>,
+}
+
+/// Evidence of two conflicting headers at the same height, both finalized by the same authorities
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct Misbehaviour {
+    pub first: FinalizedHeader,
+    pub second: FinalizedHeader,
+}
+
+/// What a verified misbehaviour proves
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct Equivocation {
+    pub height: BlockNumber,
+    pub first: Hash,
+    pub second: Hash,
+}
+
+impl Misbehaviour {
+    /// Produces the evidence if the two finalized headers conflict, that is they differ but are at the same height
+    pub fn detect<H: HeaderHasher + ?Sized>(
+        first: FinalizedHeader,
+        second: FinalizedHeader,
+        hasher: &H,
+    ) -> Option<Self> {
+        let conflicting = first.header.number == second.header.number
+            && hasher.hash_header(&first.header) != hasher.hash_header(&second.header);
+
+        conflicting.then_some(Self { first, second })
+    }
+
+    /// Verifies that both headers are at the same height, that they differ and that both are finalized by the given authorities
+    pub fn verify<K: AuthorityKeys + ?Sized, B: Backend + ?Sized>(
+        &self,
+        authorities: &K,
+        threshold: &FinalityThreshold,
+        backend: &B,
+    ) -> Result<Equivocation, ConsensusError> {
+        let height = self.first.header.number;
+        let first = backend.hash_header(&self.first.header);
+        let second = backend.hash_header(&self.second.header);
+        if self.second.header.number != height || first == second {
+            return Err(ConsensusError::InvalidMisbehaviour);
+        }
+
+        for (hash, finalized) in [(first, &self.first), (second, &self.second)] {
+            verify_signature_set(
+                authorities,
+                threshold,
+                &hash,
+                finalized.justification.clone(),
+                backend,
+            )?;
+        }
+
+        Ok(Equivocation {
+            height,
+            first,
+            second,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        backend::NativeBackend,
+        test_utils::{self, MockCommittee},
+    };
+
+    /// Header at height 7 with the given state root, signed by the given members of the committee
+    fn finalized(
+        committee: &MockCommittee,
+        signers: &[usize],
+        state_root: Hash,
+    ) -> FinalizedHeader {
+        let header = test_utils::header(7, Hash::zero(), state_root, Vec::new());
+        let justification = committee.sign_by(&NativeBackend.hash_header(&header), signers);
+
+        FinalizedHeader {
+            header,
+            justification,
+        }
+    }
+
+    #[test]
+    fn verifies_conflicting_finalized_headers() {
+        let committee = MockCommittee::new(1, 4);
+        let authorities = committee.authorities();
+        let threshold = FinalityThreshold::default();
+
+        let first = finalized(&committee, &[0, 1, 2, 3], Hash::repeat_byte(1));
+        let second = finalized(&committee, &[0, 1, 2, 3], Hash::repeat_byte(2));
+        assert!(Misbehaviour::detect(first.clone(), first.clone(), &NativeBackend).is_none());
+
+        let evidence = Misbehaviour::detect(first.clone(), second, &NativeBackend).unwrap();
+        let equivocation = evidence
+            .verify(&authorities, &threshold, &NativeBackend)
+            .unwrap();
+        assert_eq!(equivocation.height, 7);
+        assert_eq!(equivocation.first, NativeBackend.hash_header(&first.header));
+
+        // Both headers must be finalized by the authorities
+        let forged = Misbehaviour {
+            first: first.clone(),
+            second: finalized(&committee, &[0, 1], Hash::repeat_byte(2)),
+        };
+        assert!(forged
+            .verify(&authorities, &threshold, &NativeBackend)
+            .is_err());
+
+        let same = Misbehaviour {
+            first: first.clone(),
+            second: first,
+        };
+        assert!(matches!(
+            same.verify(&authorities, &threshold, &NativeBackend),
+            Err(ConsensusError::InvalidMisbehaviour)
+        ));
+    }
+}
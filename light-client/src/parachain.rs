@@ -0,0 +1,304 @@
+use crate::{
+    backend::{HeaderHasher, NativeBackend},
+    consensus::FinalityReport,
+    hash_encoded, BlockNumber, ConsensusClient, ConsensusError, GetResponse, GetSingleState, Hash,
+    Header, Proof,
+};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use primitives::{
+    state_proofs::GetResponseProof,
+    storage_keys::{StorageEntry, StorageHasher},
+};
+use scale_info::TypeInfo;
+
+/// Pallet and storage map of the parachain heads (`polkadot_runtime_parachains::paras::Heads`)
+const PARAS_PALLET: &str = "Paras";
+const PARAS_HEADS: &str = "Heads";
+
+/// Identifier of a parachain
+pub type ParaId = u32;
+
+/// Typed entry of the head of a parachain (`Paras::Heads(para_id)`), that is its SCALE encoded header
+pub fn para_head_entry(para_id: ParaId) -> StorageEntry<Vec<u8>> {
+    StorageEntry::map(
+        PARAS_PALLET,
+        PARAS_HEADS,
+        StorageHasher::Twox64Concat,
+        &para_id,
+    )
+}
+
+/// A parachain header along with the proof that it is the head of the parachain at a finalized relay chain block.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
+pub struct ParachainProof<P> {
+    pub relay_header: Header,
+    pub relay_finality: P,
+    /// State proof of `Paras::Heads(para_id)` under the relay chain header
+    pub head: GetResponseProof,
+    pub para_header: Header,
+}
+
+impl<P: Encode> core::hash::Hash for ParachainProof<P> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_encoded(self, state)
+    }
+}
+
+impl<P: Proof + Encode> Proof for ParachainProof<P> {}
+
+/// A parachain header whose state root has been verified through relay chain finality
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct VerifiedParachainHeader {
+    pub para_id: ParaId,
+    pub relay_height: BlockNumber,
+    pub height: BlockNumber,
+    pub hash: Hash,
+    pub state_root: Hash,
+}
+
+/// Consensus client of a parachain, it relies on the consensus client of its relay chain.
+pub struct ParachainConsensusClient<C, H = NativeBackend> {
+    relay: C,
+    para_id: ParaId,
+    hasher: H,
+}
+
+impl<C> ParachainConsensusClient<C>
+where
+    C: ConsensusClient,
+    C::ConsensusProof: Encode,
+{
+    pub fn new(relay: C, para_id: ParaId) -> Self {
+        Self::with_backend(NativeBackend, relay, para_id)
+    }
+}
+
+impl<C, H> ParachainConsensusClient<C, H>
+where
+    C: ConsensusClient,
+    C::ConsensusProof: Encode,
+    H: HeaderHasher,
+{
+    pub fn with_backend(hasher: H, relay: C, para_id: ParaId) -> Self {
+        Self {
+            relay,
+            para_id,
+            hasher,
+        }
+    }
+
+    pub fn para_id(&self) -> ParaId {
+        self.para_id
+    }
+
+    pub fn relay(&self) -> &C {
+        &self.relay
+    }
+
+    /// Verifies the relay chain finality and that the parachain header is the head stored in the finalized relay chain state.
+    pub fn verify_header(
+        &self,
+        proof: ParachainProof<C::ConsensusProof>,
+    ) -> Result<(VerifiedParachainHeader, FinalityReport), ConsensusError> {
+        // 1. The relay chain block must be final
+        let relay_hash = self.hasher.hash_header(&proof.relay_header);
+        let report = self
+            .relay
+            .verify_consensus(relay_hash, proof.relay_finality)?;
+
+        // 2. The head must be proven under the finalized relay chain state root
+        let entry = para_head_entry(self.para_id);
+        let response = GetResponse(proof.head);
+        if !response.verify_key_uniquness() || response.0.keys()[0] != entry.key() {
+            return Err(ConsensusError::InvalidHeadProof);
+        }
+        if response.state_root() != proof.relay_header.state_root {
+            return Err(ConsensusError::InvalidHeadProof);
+        }
+        let head = response
+            .verify_state()
+            .map_err(|_| ConsensusError::InvalidHeadProof)
+            .and_then(|value| {
+                entry
+                    .decode(&value)
+                    .map_err(|_| ConsensusError::InvalidHeadProof)
+            })?;
+
+        // 3. The parachain header must be the one stored as head
+        if proof.para_header.encode() != head {
+            return Err(ConsensusError::ParachainHeaderMismatch);
+        }
+
+        let header = VerifiedParachainHeader {
+            para_id: self.para_id,
+            relay_height: proof.relay_header.number,
+            height: proof.para_header.number,
+            hash: self.hasher.hash_header(&proof.para_header),
+            state_root: proof.para_header.state_root,
+        };
+
+        Ok((header, report))
+    }
+}
+
+impl<C, H> ConsensusClient for ParachainConsensusClient<C, H>
+where
+    C: ConsensusClient,
+    C::ConsensusProof: Encode,
+    H: HeaderHasher,
+{
+    type ConsensusProof = ParachainProof<C::ConsensusProof>;
+
+    /// The consensus state is the hash of the parachain header
+    fn verify_consensus(
+        &self,
+        consensus_state: Hash,
+        proof: Self::ConsensusProof,
+    ) -> Result<FinalityReport, ConsensusError> {
+        let (header, report) = self.verify_header(proof)?;
+        if header.hash != consensus_state {
+            return Err(ConsensusError::ParachainHeaderMismatch);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::AlephConsensusClient,
+        finality::crypto::{AlephSignature, AlephSignatureSet},
+        test_utils::{self, MockCommittee, MockState},
+    };
+
+    const PARA_ID: ParaId = 2000;
+    const RELAY_HEIGHT: BlockNumber = 20;
+
+    fn relay_client(committee: &MockCommittee) -> AlephConsensusClient {
+        let finalizer = MockCommittee::new(2, 1);
+        AlephConsensusClient::new(committee.authorities(), finalizer.authorities()[0].clone())
+    }
+
+    /// Relay chain state with the given header as head of the parachain
+    fn relay_state(para_header: &Header) -> MockState {
+        let key = para_head_entry(PARA_ID).key().to_vec();
+        MockState::new(0, [(key, para_header.encode().encode())])
+    }
+
+    /// Proof of the head, under a relay chain header with the given state root and finalized by the committee
+    fn parachain_proof(
+        committee: &MockCommittee,
+        state: &MockState,
+        state_root: Hash,
+        para_header: Header,
+    ) -> ParachainProof<AlephSignatureSet<AlephSignature>> {
+        let relay_header = test_utils::header(RELAY_HEIGHT, Hash::zero(), state_root, Vec::new());
+        let key = para_head_entry(PARA_ID).key().to_vec();
+
+        ParachainProof {
+            relay_finality: committee.sign(&NativeBackend.hash_header(&relay_header)),
+            head: state.read_proof(&[key], RELAY_HEIGHT),
+            relay_header,
+            para_header,
+        }
+    }
+
+    #[test]
+    fn verifies_heads_under_finalized_relay_state() {
+        let committee = MockCommittee::new(1, 4);
+        let client = ParachainConsensusClient::new(relay_client(&committee), PARA_ID);
+        let para_header = test_utils::header(7, Hash::zero(), Hash::repeat_byte(7), Vec::new());
+        let para_hash = NativeBackend.hash_header(&para_header);
+        let state = relay_state(&para_header);
+
+        let proof = parachain_proof(&committee, &state, state.state_root(), para_header);
+        let (header, _) = client.verify_header(proof.clone()).unwrap();
+        assert_eq!(
+            header,
+            VerifiedParachainHeader {
+                para_id: PARA_ID,
+                relay_height: RELAY_HEIGHT,
+                height: 7,
+                hash: para_hash,
+                state_root: Hash::repeat_byte(7),
+            }
+        );
+
+        assert!(client.verify_consensus(para_hash, proof.clone()).is_ok());
+        assert!(matches!(
+            client.verify_consensus(Hash::zero(), proof),
+            Err(ConsensusError::ParachainHeaderMismatch)
+        ));
+    }
+
+    #[test]
+    fn rejects_heads_of_other_parachains() {
+        let committee = MockCommittee::new(1, 4);
+        let client = ParachainConsensusClient::new(relay_client(&committee), PARA_ID + 1);
+        let para_header = test_utils::header(7, Hash::zero(), Hash::repeat_byte(7), Vec::new());
+        let state = relay_state(&para_header);
+
+        let proof = parachain_proof(&committee, &state, state.state_root(), para_header);
+        assert!(matches!(
+            client.verify_header(proof),
+            Err(ConsensusError::InvalidHeadProof)
+        ));
+    }
+
+    #[test]
+    fn rejects_heads_outside_of_the_relay_state() {
+        let committee = MockCommittee::new(1, 4);
+        let client = ParachainConsensusClient::new(relay_client(&committee), PARA_ID);
+        let para_header = test_utils::header(7, Hash::zero(), Hash::repeat_byte(7), Vec::new());
+        let state = relay_state(&para_header);
+
+        // The relay header is finalized, but the proof is not under its state root
+        let proof = parachain_proof(&committee, &state, Hash::repeat_byte(9), para_header);
+        assert!(matches!(
+            client.verify_header(proof),
+            Err(ConsensusError::InvalidHeadProof)
+        ));
+    }
+
+    #[test]
+    fn rejects_headers_other_than_the_head() {
+        let committee = MockCommittee::new(1, 4);
+        let client = ParachainConsensusClient::new(relay_client(&committee), PARA_ID);
+        let para_header = test_utils::header(7, Hash::zero(), Hash::repeat_byte(7), Vec::new());
+        let state = relay_state(&para_header);
+
+        let other = test_utils::header(7, Hash::zero(), Hash::repeat_byte(8), Vec::new());
+        let proof = parachain_proof(&committee, &state, state.state_root(), other);
+        assert!(matches!(
+            client.verify_header(proof),
+            Err(ConsensusError::ParachainHeaderMismatch)
+        ));
+
+        // The relay chain block must be finalized by its committee
+        let para_header = test_utils::header(7, Hash::zero(), Hash::repeat_byte(7), Vec::new());
+        let proof = parachain_proof(
+            &MockCommittee::new(3, 4),
+            &state,
+            state.state_root(),
+            para_header,
+        );
+        assert!(client.verify_header(proof).is_err());
+    }
+
+    #[test]
+    fn para_head_key() {
+        let key = para_head_entry(2000).key().to_vec();
+        // twox128("Paras") ++ twox128("Heads")
+        let prefix: [u8; 32] = [
+            0xcd, 0x71, 0x0b, 0x30, 0xbd, 0x2e, 0xab, 0x03, 0x52, 0xdd, 0xcc, 0x26, 0x41, 0x7a,
+            0xa1, 0x94, 0x1b, 0x3c, 0x25, 0x2f, 0xcb, 0x29, 0xd8, 0x8e, 0xff, 0x4f, 0x3d, 0xe5,
+            0xde, 0x44, 0x76, 0xc3,
+        ];
+        assert_eq!(&key[..32], &prefix[..]);
+        // Twox64Concat keeps the encoded para id at the end of the key
+        assert_eq!(&key[40..], &2000u32.encode()[..]);
+    }
+}
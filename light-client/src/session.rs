@@ -0,0 +1,325 @@
+use crate::{
+    backend::Backend,
+    consensus::{verify_signature_set, FinalityThreshold},
+    finality::crypto::{AlephSignature, AlephSignatureSet, AuthorityId},
+    GetResponse, GetSingleState, Hash, Header, SessionIndex,
+};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use primitives::{state_proofs::GetResponseProof, storage_keys::StorageEntry};
+use scale_info::TypeInfo;
+
+/// Pallet and storage value of the authorities of the next session
+const ALEPH_PALLET: &str = "Aleph";
+const NEXT_AUTHORITIES: &str = "NextAuthorities";
+
+/// Pallet and storage value of the current session index
+const SESSION_PALLET: &str = "Session";
+const CURRENT_INDEX: &str = "CurrentIndex";
+
+/// Typed entry of the authorities of the next session (`Aleph::NextAuthorities`)
+pub fn next_authorities_entry() -> StorageEntry<Vec<AuthorityId>> {
+    StorageEntry::value(ALEPH_PALLET, NEXT_AUTHORITIES)
+}
+
+/// Typed entry of the current session index (`Session::CurrentIndex`)
+pub fn session_index_entry() -> StorageEntry<SessionIndex> {
+    StorageEntry::value(SESSION_PALLET, CURRENT_INDEX)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    InvalidFinality,
+    InvalidKeysError,
+    StateRootMismatch,
+    InvalidStateProof,
+    DecodeError,
+    InvalidSession,
+    NextAuthoritiesNotSet,
+    EmptyAuthorities,
+}
+
+/// State proofs of the session index and of the next authorities, under a finalized header.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct SessionProof {
+    pub header: Header,
+    pub justification: AlephSignatureSet<AlephSignature>,
+    pub session: GetResponseProof,
+    pub next_authorities: GetResponseProof,
+}
+
+/// What has been proven at a finalized block
+struct ProvenSession {
+    session: SessionIndex,
+    next_authorities: Vec<AuthorityId>,
+}
+
+/// The authority set trusted by the light client, for a given session.
+/// Authorities are rotated trustlessly: the next set is read from the state of a block finalized by the current set,
+/// and it becomes the current set once a block of the next session is finalized by it.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct SessionAuthorities {
+    session: SessionIndex,
+    authorities: Vec<AuthorityId>,
+    next_authorities: Option<Vec<AuthorityId>>,
+    threshold: FinalityThreshold,
+}
+
+impl SessionAuthorities {
+    pub fn new(session: SessionIndex, authorities: Vec<AuthorityId>) -> Self {
+        Self {
+            session,
+            authorities,
+            next_authorities: None,
+            threshold: Default::default(),
+        }
+    }
+
+    /// Sets the finality threshold policy, by default a supermajority is required
+    pub fn with_threshold(mut self, threshold: FinalityThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the authorities of the next session, when they have already been proven
+    pub fn with_next_authorities(mut self, next_authorities: Vec<AuthorityId>) -> Self {
+        self.next_authorities = Some(next_authorities);
+        self
+    }
+
+    pub fn session(&self) -> SessionIndex {
+        self.session
+    }
+
+    pub fn authorities(&self) -> &[AuthorityId] {
+        &self.authorities
+    }
+
+    pub fn threshold(&self) -> &FinalityThreshold {
+        &self.threshold
+    }
+
+    pub fn next_authorities(&self) -> Option<&[AuthorityId]> {
+        self.next_authorities.as_deref()
+    }
+
+    /// Imports the authorities of the next session, proven under a block of the current session.
+    pub fn import_next_authorities<B: Backend + ?Sized>(
+        &mut self,
+        proof: SessionProof,
+        backend: &B,
+    ) -> Result<(), SessionError> {
+        let proven = Self::verify_session(&self.authorities, &self.threshold, proof, backend)?;
+        if proven.session != self.session {
+            return Err(SessionError::InvalidSession);
+        }
+
+        self.next_authorities = Some(proven.next_authorities);
+        Ok(())
+    }
+
+    /// Rotates the authorities at the session boundary.
+    /// The proof must be of a block of the next session, finalized by the next authorities.
+    /// The next authorities proven at that block are imported as well.
+    pub fn rotate<B: Backend + ?Sized>(
+        &mut self,
+        proof: SessionProof,
+        backend: &B,
+    ) -> Result<(), SessionError> {
+        let next_authorities = self
+            .next_authorities
+            .as_ref()
+            .ok_or(SessionError::NextAuthoritiesNotSet)?;
+
+        let proven = Self::verify_session(next_authorities, &self.threshold, proof, backend)?;
+        if Some(proven.session) != self.session.checked_add(1) {
+            return Err(SessionError::InvalidSession);
+        }
+
+        self.authorities = self
+            .next_authorities
+            .take()
+            .expect("The next authorities have been checked to exist");
+        self.next_authorities = Some(proven.next_authorities);
+        self.session = proven.session;
+
+        Ok(())
+    }
+
+    fn verify_session<B: Backend + ?Sized>(
+        authorities: &[AuthorityId],
+        threshold: &FinalityThreshold,
+        proof: SessionProof,
+        backend: &B,
+    ) -> Result<ProvenSession, SessionError> {
+        // 1. The header must be finalized by the given authorities
+        let block_hash = backend.hash_header(&proof.header);
+        verify_signature_set(
+            authorities,
+            threshold,
+            &block_hash,
+            proof.justification,
+            backend,
+        )
+        .map_err(|_| SessionError::InvalidFinality)?;
+
+        // 2. The state proofs must be under the finalized state root
+        let state_root = proof.header.state_root;
+        let session = Self::verify_value(&proof.session, session_index_entry(), state_root)?;
+        let next_authorities = Self::verify_value(
+            &proof.next_authorities,
+            next_authorities_entry(),
+            state_root,
+        )?;
+
+        if next_authorities.is_empty() {
+            return Err(SessionError::EmptyAuthorities);
+        }
+
+        Ok(ProvenSession {
+            session,
+            next_authorities,
+        })
+    }
+
+    fn verify_value<V: Decode>(
+        proof: &GetResponseProof,
+        entry: StorageEntry<V>,
+        state_root: Hash,
+    ) -> Result<V, SessionError> {
+        let response = GetResponse(proof.clone());
+        if !response.verify_key_uniquness() || proof.keys()[0] != entry.key() {
+            return Err(SessionError::InvalidKeysError);
+        }
+        if response.state_root() != state_root {
+            return Err(SessionError::StateRootMismatch);
+        }
+
+        let value = response
+            .verify_state()
+            .map_err(|_| SessionError::InvalidStateProof)?;
+        entry.decode(&value).map_err(|_| SessionError::DecodeError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::NativeBackend;
+    use crate::finality::types::NodeCount;
+    use crate::test_utils::{self, MockCommittee};
+    use primitives::state_proofs::{Proof, StateCommitment};
+    use sp_runtime::{traits::Header as HeaderT, Digest};
+
+    fn empty_proof(key: &[u8]) -> GetResponseProof {
+        let root = StateCommitment {
+            timestamp: 0,
+            state_root: Hash::zero(),
+        };
+        let proof = Proof {
+            height: 0,
+            proof: Vec::new(),
+        };
+        GetResponseProof::new(&[key.to_vec()], &root, &proof)
+    }
+
+    fn session_proof() -> SessionProof {
+        SessionProof {
+            header: Header::new(
+                1,
+                Hash::zero(),
+                Hash::zero(),
+                Hash::zero(),
+                Digest::default(),
+            ),
+            justification: AlephSignatureSet::with_size(NodeCount(0)),
+            session: empty_proof(session_index_entry().key()),
+            next_authorities: empty_proof(next_authorities_entry().key()),
+        }
+    }
+
+    #[test]
+    fn rotation_requires_next_authorities() {
+        let mut authorities = SessionAuthorities::new(0, Vec::new());
+        assert_eq!(
+            authorities.rotate(session_proof(), &NativeBackend),
+            Err(SessionError::NextAuthoritiesNotSet)
+        );
+        assert_eq!(authorities.session(), 0);
+    }
+
+    #[test]
+    fn authorities_rotate_through_finalized_state() {
+        let current = MockCommittee::new(1, 4);
+        let next = MockCommittee::new(2, 4);
+        let following = MockCommittee::new(3, 4);
+        let mut authorities = SessionAuthorities::new(5, current.authorities());
+
+        // The next authorities are proven under a block of the current session
+        let proof = test_utils::session_proof(&current, 10, 5, &next.authorities());
+        assert_eq!(
+            authorities.import_next_authorities(proof, &NativeBackend),
+            Ok(())
+        );
+        assert_eq!(
+            authorities.next_authorities(),
+            Some(&next.authorities()[..])
+        );
+
+        // They take over once they finalize a block of the next session
+        let proof = test_utils::session_proof(&next, 20, 6, &following.authorities());
+        assert_eq!(authorities.rotate(proof, &NativeBackend), Ok(()));
+        assert_eq!(authorities.session(), 6);
+        assert_eq!(authorities.authorities(), &next.authorities()[..]);
+        assert_eq!(
+            authorities.next_authorities(),
+            Some(&following.authorities()[..])
+        );
+    }
+
+    #[test]
+    fn rotation_rejects_wrong_sessions_and_old_authorities() {
+        let current = MockCommittee::new(1, 4);
+        let next = MockCommittee::new(2, 4);
+        let mut authorities = SessionAuthorities::new(5, current.authorities());
+
+        // The next authorities must be proven under a block of the current session
+        let proof = test_utils::session_proof(&current, 10, 4, &next.authorities());
+        assert_eq!(
+            authorities.import_next_authorities(proof, &NativeBackend),
+            Err(SessionError::InvalidSession)
+        );
+        assert_eq!(authorities.next_authorities(), None);
+
+        let proof = test_utils::session_proof(&current, 10, 5, &next.authorities());
+        authorities
+            .import_next_authorities(proof, &NativeBackend)
+            .unwrap();
+
+        // The rotation must be proven under a block of the next session
+        let proof = test_utils::session_proof(&next, 20, 7, &current.authorities());
+        assert_eq!(
+            authorities.rotate(proof, &NativeBackend),
+            Err(SessionError::InvalidSession)
+        );
+
+        // And finalized by the next authorities, rather than by the old ones
+        let proof = test_utils::session_proof(&current, 20, 6, &current.authorities());
+        assert_eq!(
+            authorities.rotate(proof, &NativeBackend),
+            Err(SessionError::InvalidFinality)
+        );
+        assert_eq!(authorities.session(), 5);
+        assert_eq!(authorities.authorities(), &current.authorities()[..]);
+    }
+
+    #[test]
+    fn import_requires_finality() {
+        let mut authorities = SessionAuthorities::new(0, Vec::new());
+        assert_eq!(
+            authorities.import_next_authorities(session_proof(), &NativeBackend),
+            Err(SessionError::InvalidFinality)
+        );
+        assert_eq!(authorities.next_authorities(), None);
+    }
+}
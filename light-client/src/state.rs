@@ -1,10 +1,23 @@
 use super::Hash;
-use crate::{ContractBlakeTwo256, ContractKeccak256, GetSingleState, StateProofError};
-use alloc::{borrow::ToOwned, vec::Vec};
+use crate::{BlockNumber, ContractBlakeTwo256, ContractKeccak256, GetSingleState, StateProofError};
+use alloc::vec::Vec;
 use codec::{Decode, Encode};
-use primitives::{commit_reveal::SecretKey, state_proofs::GetResponseProof};
+use primitives::{
+    commit_reveal::{CommitId, SecretKey},
+    state_proofs::GetResponseProof,
+    storage_keys::{StorageEntry, StorageHasher},
+};
+
+/// Pallet and storage value of the block timestamp (`pallet_timestamp::Now`)
+const TIMESTAMP_PALLET: &str = "Timestamp";
+const TIMESTAMP_NOW: &str = "Now";
+
+/// Pallet and storage map holding the TISPARK commitments (`pallet_commit_reveal::PhatContractCommitment`)
+const COMMIT_REVEAL_PALLET: &str = "CommitReveal";
+const COMMITMENTS_MAP: &str = "PhatContractCommitment";
 
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetResponse(pub GetResponseProof);
 
 impl GetResponse {
@@ -43,66 +56,182 @@ impl GetSingleState for GetResponse {
     }
 }
 
-/// commit (encrypted data) and nonce (iv)
-type Commitment = (Vec<u8>, Vec<u8>);
-
-#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
-pub struct ResultCommitment {
-    commitment: Commitment,
+/// Decoded `pallet_commit_reveal::TiSparkCommitment`.
+/// Bounded vectors are encoded as plain vectors, so the layout is the same as the one stored by the pallet.
+#[derive(Debug, Default, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TiSparkCommitment {
+    /// Encrypted data
+    commit: Vec<u8>,
+    metadata: Vec<u8>,
+    /// Initialization vector (nonce) used to encrypt the data
+    iv: Vec<u8>,
+    /// Revealed key, empty until the commitment is revealed
     proof: SecretKey,
 }
 
-impl ResultCommitment {
-    pub fn nonce(&self) -> &[u8] {
-        &self.commitment.1
+impl TiSparkCommitment {
+    pub fn new(commit: Vec<u8>, metadata: Vec<u8>, iv: Vec<u8>, proof: SecretKey) -> Self {
+        Self {
+            commit,
+            metadata,
+            iv,
+            proof,
+        }
     }
 
     pub fn value(&self) -> &[u8] {
-        &self.commitment.0
+        &self.commit
+    }
+
+    pub fn metadata(&self) -> &[u8] {
+        &self.metadata
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.iv
     }
 
     pub fn key(&self) -> &[u8] {
         &self.proof
     }
+
+    pub fn is_revealed(&self) -> bool {
+        !self.proof.is_empty()
+    }
 }
 
-pub type CommitId = u32;
+/// Typed entry of the commitment with the given id (`pallet_commit_reveal::PhatContractCommitment`)
+pub fn commitment_entry(id: &CommitId) -> StorageEntry<TiSparkCommitment> {
+    StorageEntry::map(
+        COMMIT_REVEAL_PALLET,
+        COMMITMENTS_MAP,
+        StorageHasher::Twox64Concat,
+        id,
+    )
+}
 
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetCommitmentResponseProof {
     height: u64,
     id: CommitId,
     proof: GetResponseProof,
 }
 
-#[allow(dead_code)]
 impl GetCommitmentResponseProof {
     pub fn response(&self) -> GetResponse {
         GetResponse(self.proof.clone())
     }
 
+    pub fn id(&self) -> CommitId {
+        self.id
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
     pub fn new(
         height: u64,
         id: CommitId,
         proof: GetResponseProof,
     ) -> Result<Self, StateProofError> {
         let commitment_response = GetCommitmentResponseProof { height, id, proof };
-        if commitment_response.response().verify_key_uniquness() {
+        if commitment_response.verify_commitment_key() {
             Ok(commitment_response)
         } else {
             Err(StateProofError::InvalidKeysError)
         }
     }
 
-    pub fn verify_commitment(&self) -> Result<ResultCommitment, StateProofError> {
+    /// The proof must be about the single storage key of the commitment
+    fn verify_commitment_key(&self) -> bool {
+        let keys = self.proof.keys();
+        keys.len() == 1 && keys[0] == commitment_entry(&self.id).key()
+    }
+
+    pub fn verify_commitment(&self) -> Result<TiSparkCommitment, StateProofError> {
+        if !self.verify_commitment_key() {
+            return Err(StateProofError::InvalidCommitId);
+        }
+
         let data = self.response().verify_state()?;
-        let commitments: Vec<ResultCommitment> =
-            Decode::decode(&mut &data[..]).map_err(|_| StateProofError::DecodeError)?;
+        commitment_entry(&self.id)
+            .decode(&data)
+            .map_err(|_| StateProofError::DecodeError)
+    }
+}
+
+/// Typed entry of the timestamp set by `pallet_timestamp` at each block, in milliseconds
+pub fn timestamp_entry() -> StorageEntry<u64> {
+    StorageEntry::value(TIMESTAMP_PALLET, TIMESTAMP_NOW)
+}
 
-        if let Some(commitment) = commitments.get(self.id as usize) {
-            Ok(commitment.to_owned())
+/// A block whose height, timestamp and state root have been verified
+#[derive(Debug, Clone, Copy, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+pub struct VerifiedBlock {
+    pub height: BlockNumber,
+    /// Timestamp in milliseconds, as stored by `pallet_timestamp`
+    pub timestamp: u64,
+    pub state_root: Hash,
+    /// Whether the block has been finalized by the emergency finalizer rather than by the committee
+    pub emergency_finalized: bool,
+}
+
+/// State proof of the `pallet_timestamp::Now` value at some block
+#[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GetTimestampResponseProof(GetResponseProof);
+
+impl GetTimestampResponseProof {
+    pub fn new(proof: GetResponseProof) -> Result<Self, StateProofError> {
+        let timestamp_response = GetTimestampResponseProof(proof);
+        if timestamp_response.verify_timestamp_key() {
+            Ok(timestamp_response)
         } else {
-            Err(StateProofError::InvalidCommitId)
+            Err(StateProofError::InvalidKeysError)
+        }
+    }
+
+    pub fn response(&self) -> GetResponse {
+        GetResponse(self.0.clone())
+    }
+
+    fn verify_timestamp_key(&self) -> bool {
+        let keys = self.0.keys();
+        keys.len() == 1 && keys[0] == timestamp_entry().key()
+    }
+
+    /// Verifies the timestamp stored at the block with the given (already verified) height and state root.
+    /// The timestamp of the state commitment must match the proven one, so that it can be trusted as well.
+    pub fn verify_block(
+        &self,
+        height: BlockNumber,
+        state_root: Hash,
+    ) -> Result<VerifiedBlock, StateProofError> {
+        if !self.verify_timestamp_key() {
+            return Err(StateProofError::InvalidKeysError);
         }
+
+        let response = self.response();
+        if response.state_root() != state_root {
+            return Err(StateProofError::StateRootMismatch);
+        }
+
+        let value = response.verify_state()?;
+        let timestamp = timestamp_entry()
+            .decode(&value)
+            .map_err(|_| StateProofError::DecodeError)?;
+
+        if timestamp != self.0.state_root().timestamp {
+            return Err(StateProofError::InvalidTimestamp);
+        }
+
+        Ok(VerifiedBlock {
+            height,
+            timestamp,
+            state_root,
+            emergency_finalized: false,
+        })
     }
 }
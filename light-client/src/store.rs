@@ -0,0 +1,404 @@
+use crate::{
+    ancestry::{AncestryError, AncestryProof, VerifiedAncestor},
+    backend::{Backend, NativeBackend},
+    consensus::{verify_signature_set, FinalityReport},
+    finality::crypto::{AlephSignature, AlephSignatureSet},
+    misbehaviour::{Equivocation, Misbehaviour},
+    session::{SessionAuthorities, SessionError, SessionProof},
+    BlockNumber, ConsensusError, Hash, Header,
+};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+#[derive(Debug)]
+pub enum StoreError {
+    NotInitialized,
+    AlreadyInitialized,
+    /// The header is not newer than the checkpoint
+    StaleHeader,
+    /// The header does not hash to the checkpoint
+    CheckpointMismatch,
+    /// Conflicting headers have been finalized, nothing is verified until the client is unfrozen
+    Frozen,
+    Consensus(ConsensusError),
+    Session(SessionError),
+    Ancestry(AncestryError),
+}
+
+/// The latest finalized header trusted by the light client, along with the authorities of its session
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct Checkpoint {
+    pub hash: Hash,
+    pub number: BlockNumber,
+    pub state_root: Hash,
+    pub authorities: SessionAuthorities,
+}
+
+/// Storage of the trusted checkpoint
+pub trait LightClientStore {
+    fn checkpoint(&self) -> Option<Checkpoint>;
+
+    fn set_checkpoint(&mut self, checkpoint: Checkpoint);
+
+    /// The evidence that froze the client, if any
+    fn misbehaviour(&self) -> Option<Misbehaviour>;
+
+    /// Records the evidence of a misbehaviour, `None` unfreezes the client
+    fn set_misbehaviour(&mut self, misbehaviour: Option<Misbehaviour>);
+}
+
+/// Store kept in memory, for off-chain relayers and tests
+#[derive(Clone, Default, Debug)]
+pub struct InMemoryStore {
+    checkpoint: Option<Checkpoint>,
+    misbehaviour: Option<Misbehaviour>,
+}
+
+impl LightClientStore for InMemoryStore {
+    fn checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoint.clone()
+    }
+
+    fn set_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.checkpoint = Some(checkpoint);
+    }
+
+    fn misbehaviour(&self) -> Option<Misbehaviour> {
+        self.misbehaviour.clone()
+    }
+
+    fn set_misbehaviour(&mut self, misbehaviour: Option<Misbehaviour>) {
+        self.misbehaviour = misbehaviour;
+    }
+}
+
+/// Keys of the values of the ink! store, under the key of the store
+const CHECKPOINT_KEY: u8 = 0;
+const MISBEHAVIOUR_KEY: u8 = 1;
+
+/// Store within the storage of an ink! contract, under the given key
+#[derive(Clone, Copy, Debug)]
+pub struct InkStore {
+    key: u32,
+}
+
+impl InkStore {
+    pub fn new(key: u32) -> Self {
+        Self { key }
+    }
+}
+
+impl LightClientStore for InkStore {
+    fn checkpoint(&self) -> Option<Checkpoint> {
+        ink_env::get_contract_storage(&(self.key, CHECKPOINT_KEY))
+            .ok()
+            .flatten()
+    }
+
+    fn set_checkpoint(&mut self, checkpoint: Checkpoint) {
+        ink_env::set_contract_storage(&(self.key, CHECKPOINT_KEY), &checkpoint);
+    }
+
+    fn misbehaviour(&self) -> Option<Misbehaviour> {
+        ink_env::get_contract_storage(&(self.key, MISBEHAVIOUR_KEY))
+            .ok()
+            .flatten()
+    }
+
+    fn set_misbehaviour(&mut self, misbehaviour: Option<Misbehaviour>) {
+        match misbehaviour {
+            Some(misbehaviour) => {
+                ink_env::set_contract_storage(&(self.key, MISBEHAVIOUR_KEY), &misbehaviour);
+            }
+            None => {
+                ink_env::clear_contract_storage(&(self.key, MISBEHAVIOUR_KEY));
+            }
+        }
+    }
+}
+
+/// Light client that keeps a trusted checkpoint in a store.
+/// The checkpoint only moves forward, through headers finalized by the authorities of its session.
+/// Older headers are verified through ancestry proofs from the checkpoint.
+pub struct CheckpointClient<S, B = NativeBackend> {
+    store: S,
+    backend: B,
+}
+
+impl<S: LightClientStore> CheckpointClient<S> {
+    pub fn new(store: S) -> Self {
+        Self::with_backend(NativeBackend, store)
+    }
+}
+
+impl<S: LightClientStore, B: Backend> CheckpointClient<S, B> {
+    pub fn with_backend(backend: B, store: S) -> Self {
+        Self { store, backend }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The trusted checkpoint, as long as the client is not frozen
+    pub fn checkpoint(&self) -> Result<Checkpoint, StoreError> {
+        if self.store.misbehaviour().is_some() {
+            return Err(StoreError::Frozen);
+        }
+
+        self.store.checkpoint().ok_or(StoreError::NotInitialized)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.store.misbehaviour().is_some()
+    }
+
+    /// Verifies evidence of conflicting headers finalized by the authorities of the checkpoint, and freezes the client
+    pub fn report_misbehaviour(
+        &mut self,
+        misbehaviour: Misbehaviour,
+    ) -> Result<Equivocation, StoreError> {
+        let checkpoint = self.checkpoint()?;
+        let authorities = &checkpoint.authorities;
+        let equivocation = misbehaviour
+            .verify(
+                authorities.authorities(),
+                authorities.threshold(),
+                &self.backend,
+            )
+            .map_err(StoreError::Consensus)?;
+
+        self.store.set_misbehaviour(Some(misbehaviour));
+        Ok(equivocation)
+    }
+
+    /// Unfreezes the client, it is up to governance to restore a trusted checkpoint
+    pub fn unfreeze(&mut self, checkpoint: Checkpoint) {
+        self.store.set_misbehaviour(None);
+        self.store.set_checkpoint(checkpoint);
+    }
+
+    /// Sets the initial checkpoint, that is trusted as it is (e.g. genesis)
+    pub fn initialize(&mut self, checkpoint: Checkpoint) -> Result<(), StoreError> {
+        if self.store.checkpoint().is_some() {
+            return Err(StoreError::AlreadyInitialized);
+        }
+
+        self.store.set_checkpoint(checkpoint);
+        Ok(())
+    }
+
+    /// Verifies a header finalized by the authorities of the checkpoint, and moves the checkpoint to it
+    pub fn import_finalized_header(
+        &mut self,
+        header: &Header,
+        justification: AlephSignatureSet<AlephSignature>,
+    ) -> Result<FinalityReport, StoreError> {
+        let mut checkpoint = self.checkpoint()?;
+        if header.number <= checkpoint.number {
+            return Err(StoreError::StaleHeader);
+        }
+
+        let hash = self.backend.hash_header(header);
+        let authorities = &checkpoint.authorities;
+        let report = verify_signature_set(
+            authorities.authorities(),
+            authorities.threshold(),
+            &hash,
+            justification,
+            &self.backend,
+        )
+        .map_err(StoreError::Consensus)?;
+
+        checkpoint.hash = hash;
+        checkpoint.number = header.number;
+        checkpoint.state_root = header.state_root;
+        self.store.set_checkpoint(checkpoint);
+
+        Ok(report)
+    }
+
+    /// Imports the authorities of the next session, proven under a header that is not older than the checkpoint.
+    /// The checkpoint moves to that header.
+    pub fn import_next_authorities(&mut self, proof: SessionProof) -> Result<(), StoreError> {
+        self.import_session(proof, |authorities, proof, backend| {
+            authorities.import_next_authorities(proof, backend)
+        })
+    }
+
+    /// Rotates the authorities of the checkpoint to the next session, through a header of the next session.
+    /// The checkpoint moves to that header.
+    pub fn rotate_session(&mut self, proof: SessionProof) -> Result<(), StoreError> {
+        self.import_session(proof, |authorities, proof, backend| {
+            authorities.rotate(proof, backend)
+        })
+    }
+
+    fn import_session(
+        &mut self,
+        proof: SessionProof,
+        import: impl FnOnce(&mut SessionAuthorities, SessionProof, &B) -> Result<(), SessionError>,
+    ) -> Result<(), StoreError> {
+        let mut checkpoint = self.checkpoint()?;
+        if proof.header.number < checkpoint.number {
+            return Err(StoreError::StaleHeader);
+        }
+
+        let hash = self.backend.hash_header(&proof.header);
+        let (number, state_root) = (proof.header.number, proof.header.state_root);
+        import(&mut checkpoint.authorities, proof, &self.backend).map_err(StoreError::Session)?;
+
+        checkpoint.hash = hash;
+        checkpoint.number = number;
+        checkpoint.state_root = state_root;
+        self.store.set_checkpoint(checkpoint);
+
+        Ok(())
+    }
+
+    /// Verifies a header older than the checkpoint, through the ancestry of the checkpoint header
+    pub fn verify_historical(
+        &self,
+        checkpoint_header: &Header,
+        ancestry: &AncestryProof,
+        height: BlockNumber,
+    ) -> Result<VerifiedAncestor, StoreError> {
+        let checkpoint = self.checkpoint()?;
+        if self.backend.hash_header(checkpoint_header) != checkpoint.hash {
+            return Err(StoreError::CheckpointMismatch);
+        }
+
+        ancestry
+            .ancestor_at(checkpoint_header, height, &self.backend)
+            .map_err(StoreError::Ancestry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        finality::crypto::AuthorityId,
+        misbehaviour::FinalizedHeader,
+        test_utils::{self, MockCommittee},
+    };
+    use sp_runtime::traits::Header as HeaderT;
+
+    /// Header whose state root is made of its number
+    fn header(number: BlockNumber, parent_hash: Hash) -> Header {
+        test_utils::header(
+            number,
+            parent_hash,
+            Hash::repeat_byte(number as u8),
+            Vec::new(),
+        )
+    }
+
+    fn client(authorities: Vec<AuthorityId>, genesis: &Header) -> CheckpointClient<InMemoryStore> {
+        let mut client = CheckpointClient::new(InMemoryStore::default());
+        client
+            .initialize(Checkpoint {
+                hash: genesis.hash(),
+                number: genesis.number,
+                state_root: genesis.state_root,
+                authorities: SessionAuthorities::new(0, authorities),
+            })
+            .unwrap();
+        client
+    }
+
+    #[test]
+    fn checkpoint_advances_monotonically() {
+        let committee = MockCommittee::new(1, 4);
+        let genesis = header(0, Hash::zero());
+        let mut client = client(committee.authorities(), &genesis);
+
+        let first = header(1, genesis.hash());
+        let second = header(2, first.hash());
+        client
+            .import_finalized_header(&second, committee.sign(&second.hash()))
+            .unwrap();
+        let checkpoint = client.checkpoint().unwrap();
+        assert_eq!(checkpoint.hash, second.hash());
+        assert_eq!(checkpoint.state_root, Hash::repeat_byte(2));
+
+        // Headers that are not newer than the checkpoint are rejected
+        assert!(matches!(
+            client.import_finalized_header(&first, committee.sign(&first.hash())),
+            Err(StoreError::StaleHeader)
+        ));
+        assert!(matches!(
+            client.import_finalized_header(&second, committee.sign(&second.hash())),
+            Err(StoreError::StaleHeader)
+        ));
+
+        // Unless they are linked to the checkpoint
+        let ancestor = client
+            .verify_historical(&second, &AncestryProof::new(vec![first.clone()]), 1)
+            .unwrap();
+        assert_eq!(ancestor.hash, first.hash());
+        assert!(matches!(
+            client.verify_historical(&first, &AncestryProof::new(Vec::new()), 1),
+            Err(StoreError::CheckpointMismatch)
+        ));
+
+        // The checkpoint does not move on invalid finality
+        let third = header(3, second.hash());
+        assert!(matches!(
+            client.import_finalized_header(&third, committee.sign_by(&third.hash(), &[0, 1])),
+            Err(StoreError::Consensus(_))
+        ));
+        assert_eq!(client.checkpoint().unwrap().number, 2);
+    }
+
+    #[test]
+    fn initializes_once() {
+        let genesis = header(0, Hash::zero());
+        let mut client = client(Vec::new(), &genesis);
+        let checkpoint = client.checkpoint().unwrap();
+
+        assert!(matches!(
+            client.initialize(checkpoint),
+            Err(StoreError::AlreadyInitialized)
+        ));
+        assert!(matches!(
+            CheckpointClient::new(InMemoryStore::default()).checkpoint(),
+            Err(StoreError::NotInitialized)
+        ));
+    }
+
+    #[test]
+    fn misbehaviour_freezes_the_client() {
+        let committee = MockCommittee::new(1, 4);
+        let genesis = header(0, Hash::zero());
+        let mut client = client(committee.authorities(), &genesis);
+        let checkpoint = client.checkpoint().unwrap();
+
+        let first = header(1, genesis.hash());
+        let conflicting = header(1, Hash::repeat_byte(9));
+        let evidence = Misbehaviour::detect(
+            FinalizedHeader {
+                justification: committee.sign(&first.hash()),
+                header: first.clone(),
+            },
+            FinalizedHeader {
+                justification: committee.sign(&conflicting.hash()),
+                header: conflicting,
+            },
+            &NativeBackend,
+        )
+        .unwrap();
+        client.report_misbehaviour(evidence).unwrap();
+
+        assert!(client.is_frozen());
+        assert!(matches!(
+            client.import_finalized_header(&first, committee.sign(&first.hash())),
+            Err(StoreError::Frozen)
+        ));
+
+        client.unfreeze(checkpoint);
+        client
+            .import_finalized_header(&first, committee.sign(&first.hash()))
+            .unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+/// Deterministic fixtures of a mock Aleph chain: committees, headers, justifications and state proofs.
+/// They let every verification path be tested offline, without data copied from a live chain.
+use crate::{
+    aura::{AuraPair, Slot},
+    backend::{HeaderHasher, NativeBackend},
+    consensus::AURA_ENGINE_ID,
+    finality::{
+        crypto::{AlephSignature, AlephSignatureSet, AuthorityId, AuthorityPair},
+        justification::{encode_as, AlephJustification, Version},
+        types::{AlephNodeIndex, NodeCount},
+    },
+    session::{next_authorities_entry, session_index_entry, SessionProof},
+    state::{commitment_entry, timestamp_entry, GetTimestampResponseProof, TiSparkCommitment},
+    BlockNumber, GetCommitmentResponseProof, Hash, Header, SessionIndex,
+};
+use alloc::vec::Vec;
+use codec::Encode;
+use primitives::{
+    commit_reveal::CommitId,
+    state_proofs::{GetResponseProof, HashAlgorithm, Proof, StateCommitment, SubstrateStateProof},
+};
+use sp_core::Pair;
+use sp_runtime::{
+    traits::{BlakeTwo256, Header as HeaderT},
+    Digest, DigestItem,
+};
+use sp_trie::{
+    recorder::Recorder, LayoutV0, MemoryDB, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut,
+};
+
+/// Formats a justification can be found in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JustificationEncoding {
+    /// Produced before the compatibility changes, without any version prefix
+    Legacy(Version),
+    Versioned(Version),
+}
+
+impl JustificationEncoding {
+    /// Every format of committee justifications
+    pub const ALL: [JustificationEncoding; 5] = [
+        JustificationEncoding::Legacy(Version(1)),
+        JustificationEncoding::Legacy(Version(2)),
+        JustificationEncoding::Versioned(Version(1)),
+        JustificationEncoding::Versioned(Version(2)),
+        JustificationEncoding::Versioned(Version(3)),
+    ];
+}
+
+/// Encodes the justification in the given format, legacy formats can only hold committee signatures
+pub fn encode_justification(
+    justification: AlephJustification,
+    encoding: JustificationEncoding,
+) -> Option<Vec<u8>> {
+    match encoding {
+        JustificationEncoding::Legacy(version) => encode_as(justification, version, false),
+        JustificationEncoding::Versioned(version) => encode_as(justification, version, true),
+    }
+}
+
+/// A committee whose keys are derived from a seed, so that the same committee is built at every run
+pub struct MockCommittee {
+    pairs: Vec<AuthorityPair>,
+}
+
+impl MockCommittee {
+    /// Committees built with different seeds do not share any authority
+    pub fn new(seed: u8, size: u8) -> Self {
+        let pairs = (0..size)
+            .map(|index| {
+                let mut raw = [seed; 32];
+                raw[0] = index;
+                AuthorityPair::from_seed(&raw)
+            })
+            .collect();
+
+        Self { pairs }
+    }
+
+    pub fn pairs(&self) -> &[AuthorityPair] {
+        &self.pairs
+    }
+
+    pub fn authorities(&self) -> Vec<AuthorityId> {
+        self.pairs.iter().map(|pair| pair.public()).collect()
+    }
+
+    /// Signatures of the given members over a block hash
+    pub fn sign_by(&self, hash: &Hash, signers: &[usize]) -> AlephSignatureSet<AlephSignature> {
+        signers.iter().fold(
+            AlephSignatureSet::with_size(NodeCount(self.pairs.len())),
+            |set, index| {
+                let signature = AlephSignature::from(self.pairs[*index].sign(hash.as_bytes()));
+                set.add_signature(&signature, AlephNodeIndex(*index))
+            },
+        )
+    }
+
+    /// Signatures of the whole committee over a block hash
+    pub fn sign(&self, hash: &Hash) -> AlephSignatureSet<AlephSignature> {
+        let signers: Vec<usize> = (0..self.pairs.len()).collect();
+        self.sign_by(hash, &signers)
+    }
+
+    /// Justification of the header signed by the whole committee
+    pub fn justification(&self, header: &Header) -> AlephJustification {
+        AlephJustification::CommitteeMultisignature(self.sign(&NativeBackend.hash_header(header)))
+    }
+}
+
+/// Justification of the header signed by the emergency finalizer
+pub fn emergency_justification(finalizer: &AuthorityPair, header: &Header) -> AlephJustification {
+    let hash = NativeBackend.hash_header(header);
+    AlephJustification::EmergencySignature(finalizer.sign(hash.as_bytes()))
+}
+
+/// Proof of the session index and of the next authorities, under a block finalized by the committee
+pub fn session_proof(
+    committee: &MockCommittee,
+    number: BlockNumber,
+    session: SessionIndex,
+    next_authorities: &[AuthorityId],
+) -> SessionProof {
+    let session_key = session_index_entry().key().to_vec();
+    let next_authorities_key = next_authorities_entry().key().to_vec();
+    let state = MockState::new(
+        0,
+        [
+            (session_key.clone(), session.encode()),
+            (next_authorities_key.clone(), next_authorities.encode()),
+        ],
+    );
+
+    let header = header(number, Hash::zero(), state.state_root(), Vec::new());
+    let justification = committee.sign(&NativeBackend.hash_header(&header));
+
+    SessionProof {
+        header,
+        justification,
+        session: state.read_proof(&[session_key], number),
+        next_authorities: state.read_proof(&[next_authorities_key], number),
+    }
+}
+
+/// Builds a header with the given digest items and an empty extrinsics root
+pub fn header(
+    number: BlockNumber,
+    parent_hash: Hash,
+    state_root: Hash,
+    logs: Vec<DigestItem>,
+) -> Header {
+    Header::new(
+        number,
+        Hash::zero(),
+        state_root,
+        parent_hash,
+        Digest { logs },
+    )
+}
+
+/// Header of the given slot, sealed by its Aura author
+pub fn sealed_header(author: &AuraPair, number: BlockNumber, slot: Slot) -> Header {
+    let mut header = header(
+        number,
+        Hash::zero(),
+        Hash::zero(),
+        vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())],
+    );
+    let pre_hash = NativeBackend.hash_header(&header);
+    let seal = author.sign(pre_hash.as_bytes());
+    header
+        .digest_mut()
+        .push(DigestItem::Seal(AURA_ENGINE_ID, seal.encode()));
+
+    header
+}
+
+/// Builds a chain of descendants of the given header, from the child to the last one
+pub fn descendants(parent: &Header, count: u32) -> Vec<Header> {
+    let mut chain: Vec<Header> = Vec::new();
+    for _ in 0..count {
+        let last = chain.last().unwrap_or(parent);
+        let child = header(
+            last.number + 1,
+            NativeBackend.hash_header(last),
+            Hash::zero(),
+            Vec::new(),
+        );
+        chain.push(child);
+    }
+
+    chain
+}
+
+/// In memory state trie, it always holds the block timestamp set by `pallet_timestamp`
+pub struct MockState {
+    db: MemoryDB<BlakeTwo256>,
+    root: Hash,
+    timestamp: u64,
+}
+
+impl MockState {
+    pub fn new(timestamp: u64, entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> Self {
+        let mut db = MemoryDB::default();
+        let mut root = Hash::default();
+        {
+            let mut trie =
+                TrieDBMutBuilder::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut root).build();
+            trie.insert(timestamp_entry().key(), &timestamp.encode())
+                .expect("Inserting into an in memory trie does not fail");
+            for (key, value) in entries {
+                trie.insert(&key, &value)
+                    .expect("Inserting into an in memory trie does not fail");
+            }
+        }
+
+        Self {
+            db,
+            root,
+            timestamp,
+        }
+    }
+
+    /// State holding the given commitments, as stored by `pallet_commit_reveal`
+    pub fn with_commitments(
+        timestamp: u64,
+        commitments: impl IntoIterator<Item = (CommitId, TiSparkCommitment)>,
+    ) -> Self {
+        let entries = commitments
+            .into_iter()
+            .map(|(id, commitment)| (commitment_entry(&id).key().to_vec(), commitment.encode()));
+
+        Self::new(timestamp, entries)
+    }
+
+    pub fn state_root(&self) -> Hash {
+        self.root
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Read proof of the given keys, with only the trie nodes visited while reading them
+    pub fn read_proof(&self, keys: &[Vec<u8>], height: BlockNumber) -> GetResponseProof {
+        let recorder = Recorder::<BlakeTwo256>::default();
+        {
+            let mut trie_recorder = recorder.as_trie_recorder(self.root);
+            let trie = TrieDBBuilder::<LayoutV0<BlakeTwo256>>::new(&self.db, &self.root)
+                .with_recorder(&mut trie_recorder)
+                .build();
+            for key in keys {
+                trie.get(key)
+                    .expect("The in memory trie holds all of its nodes");
+            }
+        }
+
+        let proof = SubstrateStateProof {
+            hasher: HashAlgorithm::Blake2,
+            storage_proof: recorder.drain_storage_proof().into_iter_nodes().collect(),
+        };
+        let root = StateCommitment {
+            timestamp: self.timestamp,
+            state_root: self.root,
+        };
+        let proof = Proof {
+            height: height.into(),
+            proof: proof.encode(),
+        };
+
+        GetResponseProof::new(keys, &root, &proof)
+    }
+
+    pub fn commitment_proof(
+        &self,
+        id: CommitId,
+        height: BlockNumber,
+    ) -> GetCommitmentResponseProof {
+        let keys = [commitment_entry(&id).key().to_vec()];
+        GetCommitmentResponseProof::new(height.into(), id, self.read_proof(&keys, height))
+            .expect("The proof is about the commitment key")
+    }
+
+    pub fn timestamp_proof(&self, height: BlockNumber) -> GetTimestampResponseProof {
+        let keys = [timestamp_entry().key().to_vec()];
+        GetTimestampResponseProof::new(self.read_proof(&keys, height))
+            .expect("The proof is about the timestamp key")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::{AlephConsensusClient, Finality},
+        finality::justification::backwards_compatible_decode,
+    };
+
+    #[test]
+    fn justifications_verify_in_every_encoding() {
+        let committee = MockCommittee::new(1, 4);
+        let finalizer = MockCommittee::new(2, 1);
+        let client =
+            AlephConsensusClient::new(committee.authorities(), finalizer.authorities()[0].clone());
+
+        let genesis = header(0, Hash::zero(), Hash::zero(), Vec::new());
+        let block = descendants(&genesis, 3).pop().unwrap();
+        assert_eq!(block.number, 3);
+        let hash = NativeBackend.hash_header(&block);
+
+        for encoding in JustificationEncoding::ALL {
+            let encoded = encode_justification(committee.justification(&block), encoding).unwrap();
+            let justification = backwards_compatible_decode(encoded).unwrap();
+            assert!(matches!(
+                client.verify_justification(block.number, hash, justification, None),
+                Ok(Finality::Committee(_))
+            ));
+        }
+
+        // Only the current format holds emergency signatures
+        let emergency = emergency_justification(&finalizer.pairs()[0], &block);
+        assert!(
+            encode_justification(emergency.clone(), JustificationEncoding::Legacy(Version(2)))
+                .is_none()
+        );
+        assert!(
+            encode_justification(emergency, JustificationEncoding::Versioned(Version(3))).is_some()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn justifications_round_trip_through_hex_json() {
+        let committee = MockCommittee::new(1, 4);
+        let block = header(1, Hash::zero(), Hash::zero(), Vec::new());
+        let justification = committee.justification(&block);
+
+        let json = serde_json::to_string(&justification).unwrap();
+        assert!(json.starts_with("\"0x0300"));
+        assert_eq!(
+            serde_json::from_str::<AlephJustification>(&json).unwrap(),
+            justification
+        );
+    }
+
+    #[test]
+    fn state_proofs_verify_against_the_state_root() {
+        let id = Hash::repeat_byte(1);
+        let commitment = TiSparkCommitment::new(vec![1, 2, 3], vec![4], vec![5; 12], Vec::new());
+        let state = MockState::with_commitments(1_700_000_000_000, [(id, commitment.clone())]);
+
+        let proof = state.commitment_proof(id, 10);
+        assert_eq!(proof.response().state_root(), state.state_root());
+        assert_eq!(proof.verify_commitment(), Ok(commitment));
+
+        let block = state
+            .timestamp_proof(10)
+            .verify_block(10, state.state_root())
+            .unwrap();
+        assert_eq!(block.timestamp, state.timestamp());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    finality::justification::{
+        backwards_compatible_decode, versioned_decode, AlephJustification, DecodeError, Version,
+    },
+    BlockNumber, FinalityVersion, SessionIndex, VersionChange,
+};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VersionScheduleError {
+    /// A version change must happen at a later session than the ones already scheduled
+    NonIncreasingSession,
+    /// Sessions can not be computed without a session period
+    MissingSessionPeriod,
+}
+
+/// Schedule of the finality versions by session.
+/// Before the first version change, justifications are decoded in any of the legacy formats.
+/// From the session of a version change on, they must be encoded with exactly the incoming version.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub struct FinalityVersionSchedule {
+    session_period: BlockNumber,
+    changes: Vec<(SessionIndex, FinalityVersion)>,
+}
+
+impl FinalityVersionSchedule {
+    pub fn new(session_period: BlockNumber) -> Self {
+        Self {
+            session_period,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn session_period(&self) -> BlockNumber {
+        self.session_period
+    }
+
+    /// Schedules a version change, changes must be scheduled in increasing session order
+    pub fn schedule(&mut self, change: VersionChange) -> Result<(), VersionScheduleError> {
+        if self.session_period == 0 {
+            return Err(VersionScheduleError::MissingSessionPeriod);
+        }
+        if let Some((session, _)) = self.changes.last() {
+            if change.session <= *session {
+                return Err(VersionScheduleError::NonIncreasingSession);
+            }
+        }
+
+        self.changes.push((change.session, change.incoming));
+        Ok(())
+    }
+
+    /// Session of a block
+    pub fn session_of(&self, block: BlockNumber) -> SessionIndex {
+        block.checked_div(self.session_period).unwrap_or_default()
+    }
+
+    /// Finality version active at a session. `None` means that no switch-over has happened yet.
+    pub fn version_at(&self, session: SessionIndex) -> Option<FinalityVersion> {
+        self.changes
+            .iter()
+            .rev()
+            .find(|(change_session, _)| *change_session <= session)
+            .map(|(_, version)| *version)
+    }
+
+    /// Decodes the justification of a block, strictly with the version active at the block's session.
+    pub fn decode_justification(
+        &self,
+        block: BlockNumber,
+        justification: Vec<u8>,
+    ) -> Result<AlephJustification, DecodeError> {
+        match self.version_at(self.session_of(block)) {
+            None => backwards_compatible_decode(justification),
+            Some(version) => {
+                let version = u16::try_from(version).map_err(|_| DecodeError::BadFormat)?;
+                versioned_decode(justification, Version(version))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{finality::justification::versioned_encode, test_utils::MockCommittee, Hash};
+
+    const SESSION_PERIOD: BlockNumber = 900;
+
+    fn justification() -> AlephJustification {
+        AlephJustification::CommitteeMultisignature(MockCommittee::new(1, 4).sign(&Hash::zero()))
+    }
+
+    #[test]
+    fn tracks_versions_by_session() {
+        let mut schedule = FinalityVersionSchedule::new(SESSION_PERIOD);
+        schedule
+            .schedule(VersionChange {
+                incoming: 2,
+                session: 3,
+            })
+            .unwrap();
+        schedule
+            .schedule(VersionChange {
+                incoming: 3,
+                session: 5,
+            })
+            .unwrap();
+
+        assert_eq!(
+            schedule.schedule(VersionChange {
+                incoming: 4,
+                session: 5,
+            }),
+            Err(VersionScheduleError::NonIncreasingSession)
+        );
+        assert_eq!(schedule.version_at(2), None);
+        assert_eq!(schedule.version_at(3), Some(2));
+        assert_eq!(schedule.version_at(4), Some(2));
+        assert_eq!(schedule.version_at(10), Some(3));
+        assert_eq!(schedule.session_of(5 * SESSION_PERIOD - 1), 4);
+    }
+
+    #[test]
+    fn decodes_strictly_after_switch_over() {
+        let mut schedule = FinalityVersionSchedule::new(SESSION_PERIOD);
+        schedule
+            .schedule(VersionChange {
+                incoming: 3,
+                session: 1,
+            })
+            .unwrap();
+
+        // Legacy (pre-compatibility) justifications are accepted before the switch-over only
+        let legacy = match justification() {
+            AlephJustification::CommitteeMultisignature(signatures) => signatures.encode(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            schedule.decode_justification(0, legacy.clone()),
+            Ok(justification())
+        );
+        assert!(schedule
+            .decode_justification(SESSION_PERIOD, legacy)
+            .is_err());
+
+        let versioned = versioned_encode(justification());
+        assert_eq!(
+            schedule.decode_justification(SESSION_PERIOD, versioned),
+            Ok(justification())
+        );
+
+        schedule
+            .schedule(VersionChange {
+                incoming: 4,
+                session: 2,
+            })
+            .unwrap();
+        assert_eq!(
+            schedule.decode_justification(2 * SESSION_PERIOD, versioned_encode(justification())),
+            Err(DecodeError::UnexpectedVersion(Version(3)))
+        );
+    }
+}
@@ -26,17 +26,21 @@ pub trait Commitment<C: Encode, Metadata> {
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Default, Debug, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct DataToCommit<T> {
     /// The metadata that identified the data
     metadata: T,
     /// The actual committed specific data
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
     data: EncryptedData,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Default, Debug, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Commit<Metadata> {
     id: CommitId,
     data: DataToCommit<Metadata>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
     iv: Vec<u8>,
 }
 
@@ -96,8 +100,10 @@ impl<Metadata: Clone> Commit<Metadata> {
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, Default, Debug, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RevealProof {
     pub commit_id: CommitId,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
     pub secret: SecretKey,
 }
 
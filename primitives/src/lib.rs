@@ -7,6 +7,13 @@ pub mod commit_reveal;
 /// Phala ISMP
 pub mod state_proofs;
 
+/// FRAME storage keys and typed values
+pub mod storage_keys;
+
+/// Hex representation of bytes and SCALE encoded values
+#[cfg(feature = "serde")]
+pub mod serde_hex;
+
 pub const MAX_COMMITMENT_SIZE: u32 = 2048 / 8;
 pub const ALGO_SIZE: u32 = 256 / 8;
 pub const IV_SIZE: u32 = 96 / 8;
@@ -0,0 +1,133 @@
+use alloc::{format, string::String, vec::Vec};
+use codec::{Decode, Encode};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// The `0x` prefix is optional
+fn from_hex<E: Error>(hex: &str) -> Result<Vec<u8>, E> {
+    hex::decode(hex.strip_prefix("0x").unwrap_or(hex)).map_err(E::custom)
+}
+
+fn decode_scale<T: Decode, E: Error>(hex: &str) -> Result<T, E> {
+    let encoded = from_hex::<E>(hex)?;
+    T::decode(&mut &encoded[..]).map_err(|_| E::custom("invalid SCALE encoding"))
+}
+
+/// Raw bytes as a hex string
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        bytes: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(bytes.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        from_hex(&String::deserialize(deserializer)?)
+    }
+}
+
+/// A sequence of raw bytes as a list of hex strings
+pub mod bytes_seq {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|item| to_hex(item.as_ref())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|item| from_hex(item))
+            .collect()
+    }
+}
+
+/// Any SCALE encoded value as a hex string
+pub mod scale {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Encode>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(&value.encode()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Decode>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        decode_scale(&String::deserialize(deserializer)?)
+    }
+}
+
+/// A sequence of SCALE encoded values as a list of hex strings
+pub mod scale_seq {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Encode>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|item| to_hex(&item.encode())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Decode>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|item| decode_scale(item))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commit_reveal::{Commit, RevealProof},
+        state_proofs::{GetResponseProof, Proof, StateCommitment},
+    };
+    use sp_core::H256;
+
+    #[test]
+    fn proofs_round_trip_through_hex_json() {
+        let reveal = RevealProof {
+            commit_id: H256::repeat_byte(1),
+            secret: vec![0xab; 32],
+        };
+        let json = serde_json::to_string(&reveal).unwrap();
+        assert!(json.contains(&format!("0x{}", "ab".repeat(32))));
+        assert_eq!(serde_json::from_str::<RevealProof>(&json).unwrap(), reveal);
+
+        let commit = Commit::new(H256::repeat_byte(2), vec![1, 2, 3], vec![4; 12], 7u32);
+        let json = serde_json::to_string(&commit).unwrap();
+        assert_eq!(serde_json::from_str::<Commit<u32>>(&json).unwrap(), commit);
+
+        let root = StateCommitment {
+            timestamp: 1_700_000_000_000,
+            state_root: H256::repeat_byte(3),
+        };
+        let proof = Proof {
+            height: 10,
+            proof: vec![5, 6],
+        };
+        let response = GetResponseProof::new(&[vec![7, 8], vec![9]], &root, &proof);
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"0x0708\""));
+        assert_eq!(
+            serde_json::from_str::<GetResponseProof>(&json).unwrap(),
+            response
+        );
+    }
+}
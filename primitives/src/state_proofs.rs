@@ -13,9 +13,9 @@ pub enum Error {
 /// height. Optionally holds a commitment to the ISMP request/response trie if supported by the
 /// state machine.
 #[derive(Debug, Clone, Copy, Encode, Decode, scale_info::TypeInfo, PartialEq, Hash, Eq)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StateCommitment {
-    /// Timestamp in seconds
+    /// Timestamp in milliseconds
     pub timestamp: u64,
     /// Root hash of the global state trie.
     pub state_root: H256,
@@ -23,11 +23,12 @@ pub struct StateCommitment {
 
 /// Proof holds the relevant proof data.
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Proof {
     /// State height
     pub height: u64,
     /// Scale encoded proof
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes"))]
     pub proof: Vec<u8>,
 }
 
@@ -50,7 +51,9 @@ pub struct SubstrateStateProof {
 }
 
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GetResponseProof {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::bytes_seq"))]
     keys: Vec<Vec<u8>>,
     root: StateCommitment,
     proof: Proof,
@@ -73,6 +76,11 @@ impl GetResponseProof {
         &self.root
     }
 
+    /// Height of the block the proof has been produced at
+    pub fn height(&self) -> u64 {
+        self.proof.height
+    }
+
     pub fn verify_state_proof<Keccak: Hasher<Out = H256>, Blake2: Hasher<Out = H256>>(
         &self,
     ) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, Error> {
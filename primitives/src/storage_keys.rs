@@ -0,0 +1,269 @@
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use sp_core_hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    MissingValue,
+    DecodeError,
+    InvalidKeyLength,
+}
+
+/// Hashers that FRAME uses to build the final part of a storage key.
+/// Mirrors `frame_support::StorageHasher` so keys can be computed without depending on FRAME.
+#[derive(Debug, Clone, Copy, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+pub enum StorageHasher {
+    Blake2_128,
+    Blake2_256,
+    Blake2_128Concat,
+    Twox128,
+    Twox256,
+    Twox64Concat,
+    Identity,
+}
+
+impl StorageHasher {
+    /// Hashes some (encoded) key with the hasher.
+    pub fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            StorageHasher::Blake2_128 => blake2_128(data).to_vec(),
+            StorageHasher::Blake2_256 => blake2_256(data).to_vec(),
+            StorageHasher::Blake2_128Concat => [&blake2_128(data)[..], data].concat(),
+            StorageHasher::Twox128 => twox_128(data).to_vec(),
+            StorageHasher::Twox256 => twox_256(data).to_vec(),
+            StorageHasher::Twox64Concat => [&twox_64(data)[..], data].concat(),
+            StorageHasher::Identity => data.to_vec(),
+        }
+    }
+
+    /// Whether the original key can be recovered from the hashed key.
+    pub fn is_concat(&self) -> bool {
+        matches!(
+            self,
+            StorageHasher::Blake2_128Concat | StorageHasher::Twox64Concat | StorageHasher::Identity
+        )
+    }
+
+    /// Size of the hash part of the key (the part that precedes the concatenated key, if any).
+    pub fn hash_len(&self) -> usize {
+        match self {
+            StorageHasher::Blake2_128 | StorageHasher::Twox128 => 16,
+            StorageHasher::Blake2_256 | StorageHasher::Twox256 => 32,
+            StorageHasher::Blake2_128Concat => 16,
+            StorageHasher::Twox64Concat => 8,
+            StorageHasher::Identity => 0,
+        }
+    }
+}
+
+/// The `twox128(pallet) ++ twox128(item)` prefix shared by every key of a storage item.
+pub fn storage_prefix(pallet: &str, item: &str) -> [u8; 32] {
+    let mut prefix = [0u8; 32];
+    prefix[..16].copy_from_slice(&twox_128(pallet.as_bytes()));
+    prefix[16..].copy_from_slice(&twox_128(item.as_bytes()));
+    prefix
+}
+
+/// Builder of FRAME storage keys, from the pallet and storage item names.
+/// Each call to `key` appends a hashed key, so it covers values, maps, double maps and n-maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageKeyBuilder {
+    key: Vec<u8>,
+}
+
+impl StorageKeyBuilder {
+    pub fn new(pallet: &str, item: &str) -> Self {
+        Self {
+            key: storage_prefix(pallet, item).to_vec(),
+        }
+    }
+
+    /// Appends a key that is SCALE encoded and then hashed
+    pub fn key<K: Encode>(self, hasher: StorageHasher, key: &K) -> Self {
+        key.using_encoded(|encoded| self.encoded_key(hasher, encoded))
+    }
+
+    /// Appends an already encoded key
+    pub fn encoded_key(mut self, hasher: StorageHasher, encoded: &[u8]) -> Self {
+        self.key.extend(hasher.hash(encoded));
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        self.key
+    }
+}
+
+/// Storage key of a `StorageValue`
+pub fn storage_value_key(pallet: &str, item: &str) -> Vec<u8> {
+    StorageKeyBuilder::new(pallet, item).build()
+}
+
+/// Storage key of a `StorageMap` entry
+pub fn storage_map_key<K: Encode>(
+    pallet: &str,
+    item: &str,
+    hasher: StorageHasher,
+    key: &K,
+) -> Vec<u8> {
+    StorageKeyBuilder::new(pallet, item)
+        .key(hasher, key)
+        .build()
+}
+
+/// Storage key of a `StorageDoubleMap` entry
+pub fn storage_double_map_key<K1: Encode, K2: Encode>(
+    pallet: &str,
+    item: &str,
+    first: (StorageHasher, &K1),
+    second: (StorageHasher, &K2),
+) -> Vec<u8> {
+    StorageKeyBuilder::new(pallet, item)
+        .key(first.0, first.1)
+        .key(second.0, second.1)
+        .build()
+}
+
+/// Storage key of a `StorageNMap` entry, each key is provided already SCALE encoded
+pub fn storage_n_map_key(pallet: &str, item: &str, keys: &[(StorageHasher, Vec<u8>)]) -> Vec<u8> {
+    keys.iter()
+        .fold(
+            StorageKeyBuilder::new(pallet, item),
+            |builder, (hasher, key)| builder.encoded_key(*hasher, key),
+        )
+        .build()
+}
+
+/// Recovers the original map key from a full storage key, for concat hashers only.
+pub fn decode_map_key<K: Decode>(storage_key: &[u8], hasher: StorageHasher) -> Result<K, Error> {
+    if !hasher.is_concat() {
+        return Err(Error::InvalidKeyLength);
+    }
+    let start = 32 + hasher.hash_len();
+    if storage_key.len() < start {
+        return Err(Error::InvalidKeyLength);
+    }
+
+    Decode::decode(&mut &storage_key[start..]).map_err(|_| Error::DecodeError)
+}
+
+/// A typed storage entry, it binds a storage key to the type of the value stored under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageEntry<V> {
+    key: Vec<u8>,
+    _value: PhantomData<V>,
+}
+
+impl<V: Decode> StorageEntry<V> {
+    pub fn new(key: Vec<u8>) -> Self {
+        Self {
+            key,
+            _value: PhantomData,
+        }
+    }
+
+    pub fn value(pallet: &str, item: &str) -> Self {
+        Self::new(storage_value_key(pallet, item))
+    }
+
+    pub fn map<K: Encode>(pallet: &str, item: &str, hasher: StorageHasher, key: &K) -> Self {
+        Self::new(storage_map_key(pallet, item, hasher, key))
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Decodes a raw value as read from the storage (or from a storage proof).
+    pub fn decode(&self, raw: &[u8]) -> Result<V, Error> {
+        Decode::decode(&mut &raw[..]).map_err(|_| Error::DecodeError)
+    }
+
+    /// Decodes a value that may be missing, as an `OptionQuery` would.
+    pub fn decode_optional(&self, raw: Option<&[u8]>) -> Result<Option<V>, Error> {
+        raw.map(|raw| self.decode(raw)).transpose()
+    }
+
+    /// Decodes a value that is expected to exist.
+    pub fn decode_required(&self, raw: Option<&[u8]>) -> Result<V, Error> {
+        self.decode_optional(raw)?.ok_or(Error::MissingValue)
+    }
+}
+
+impl<V: Decode + Default> StorageEntry<V> {
+    /// Decodes a value falling back to its default, as a `ValueQuery` would.
+    pub fn decode_or_default(&self, raw: Option<&[u8]>) -> Result<V, Error> {
+        Ok(self.decode_optional(raw)?.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sp_core::H256;
+
+    #[test]
+    fn builds_storage_value_key() {
+        // twox128("Aleph") ++ twox128("Authorities")
+        let expected: [u8; 32] = [
+            0xd3, 0x9f, 0x95, 0x08, 0x31, 0x49, 0x57, 0xb7, 0x4c, 0x78, 0x7c, 0x4a, 0xbb, 0x8c,
+            0x95, 0xbb, 0x5e, 0x06, 0x21, 0xc4, 0x86, 0x9a, 0xa6, 0x0c, 0x02, 0xbe, 0x9a, 0xdc,
+            0xc9, 0x8a, 0x0d, 0x1d,
+        ];
+        assert_eq!(storage_value_key("Aleph", "Authorities"), expected.to_vec());
+    }
+
+    #[test]
+    fn builds_storage_map_key() {
+        let id = H256::repeat_byte(7);
+        let key = storage_map_key(
+            "CommitReveal",
+            "PhatContractCommitment",
+            StorageHasher::Twox64Concat,
+            &id,
+        );
+
+        // twox128("CommitReveal") ++ twox128("PhatContractCommitment")
+        let prefix: [u8; 32] = [
+            0xa4, 0x5f, 0x72, 0x30, 0x93, 0x2f, 0xe9, 0xd5, 0xeb, 0xc8, 0x46, 0xb8, 0x73, 0xec,
+            0xd5, 0x3f, 0xe2, 0xb9, 0x63, 0x43, 0x2a, 0xe5, 0x50, 0x77, 0x2d, 0xaa, 0x14, 0xb5,
+            0xf8, 0xe6, 0xe3, 0x97,
+        ];
+        assert_eq!(&key[..32], &prefix[..]);
+        assert_eq!(&key[32..40], &twox_64(id.as_bytes())[..]);
+        assert_eq!(
+            decode_map_key::<H256>(&key, StorageHasher::Twox64Concat),
+            Ok(id)
+        );
+    }
+
+    #[test]
+    fn builds_double_and_n_map_keys() {
+        let double = storage_double_map_key(
+            "Pallet",
+            "Item",
+            (StorageHasher::Blake2_128Concat, &1u32),
+            (StorageHasher::Identity, &2u64),
+        );
+        let n_map = storage_n_map_key(
+            "Pallet",
+            "Item",
+            &[
+                (StorageHasher::Blake2_128Concat, 1u32.encode()),
+                (StorageHasher::Identity, 2u64.encode()),
+            ],
+        );
+        assert_eq!(double, n_map);
+        assert_eq!(double.len(), 32 + 16 + 4 + 8);
+    }
+
+    #[test]
+    fn decodes_typed_values() {
+        let entry = StorageEntry::<u64>::value("Timestamp", "Now");
+        assert_eq!(entry.decode_required(Some(&42u64.encode())), Ok(42));
+        assert_eq!(entry.decode_required(None), Err(Error::MissingValue));
+        assert_eq!(entry.decode_or_default(None), Ok(0));
+        assert_eq!(entry.decode(&[1u8]), Err(Error::DecodeError));
+    }
+}